@@ -0,0 +1,82 @@
+//! Compares naive and incremental rule matching under per-tick fact churn.
+//!
+//! Run with `cargo bench --bench matching`.
+
+use agentropic_cognition::reasoning::{MatchStrategy, ReasoningEngine, Rule};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RULES: usize = 200;
+const SENSORS: usize = 40;
+const TICKS: usize = 10;
+const RESIDENT: usize = 20_000;
+const CHURN: usize = 2_000;
+
+fn engine(strategy: MatchStrategy) -> ReasoningEngine {
+    let mut engine = ReasoningEngine::with_matching(strategy);
+    for i in 0..RULES {
+        engine.add_rule(
+            Rule::new(format!("rule_{}", i))
+                .with_condition(format!("reading(S, level_{})", i % 10))
+                .with_condition(format!("zone(S, zone_{})", i % 25))
+                .with_conclusion(format!("alarm_{}(S)", i)),
+        );
+    }
+    for s in 0..SENSORS {
+        engine.add_fact(format!("zone(s{}, zone_{})", s, s % 25));
+    }
+    engine
+}
+
+/// Each tick every sensor retracts its previous reading and asserts a new one
+fn run(strategy: MatchStrategy) -> Duration {
+    let mut engine = engine(strategy);
+    let start = Instant::now();
+    for tick in 0..TICKS {
+        for s in 0..SENSORS {
+            if tick > 0 {
+                engine.retract_fact(&format!("reading(s{}, level_{})", s, (tick - 1 + s) % 10));
+            }
+            engine.add_fact(format!("reading(s{}, level_{})", s, (tick + s) % 10));
+        }
+        black_box(engine.infer());
+    }
+    start.elapsed()
+}
+
+/// Retract and re-assert one fact against a large working memory
+fn retract_readd(strategy: MatchStrategy) -> Duration {
+    let mut engine = engine(strategy);
+    for i in 0..RESIDENT {
+        engine.add_fact(format!("log(entry_{})", i));
+    }
+    engine.add_fact("reading(s0, level_0)");
+    engine.infer();
+    let start = Instant::now();
+    for _ in 0..CHURN {
+        engine.retract_fact("reading(s0, level_0)");
+        engine.add_fact("reading(s0, level_0)");
+        black_box(engine.infer());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let naive = run(MatchStrategy::Naive);
+    let incremental = run(MatchStrategy::Incremental);
+
+    println!("{} rules, {} sensors, {} ticks", RULES, SENSORS, TICKS);
+    println!("naive:       {:>10.2?}", naive);
+    println!("incremental: {:>10.2?}", incremental);
+    println!(
+        "speedup:     {:>10.1}x",
+        naive.as_secs_f64() / incremental.as_secs_f64()
+    );
+
+    println!();
+    println!("retract/re-add x{} with {} resident facts", CHURN, RESIDENT);
+    println!(
+        "incremental: {:>10.2?}",
+        retract_readd(MatchStrategy::Incremental)
+    );
+}
@@ -1,38 +1,103 @@
+use super::Value;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Where a belief came from
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Source {
+    /// Not recorded
+    #[default]
+    Unknown,
+    /// A named sensor
+    Sensor(String),
+    /// Another agent
+    Agent(String),
+    /// Derived by the named rule
+    Inference(String),
+    /// A human user
+    User(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Unknown => write!(f, "unknown"),
+            Source::Sensor(name) => write!(f, "sensor:{}", name),
+            Source::Agent(name) => write!(f, "agent:{}", name),
+            Source::Inference(rule) => write!(f, "inference:{}", rule),
+            Source::User(name) => write!(f, "user:{}", name),
+        }
+    }
+}
 
 /// A belief represents knowledge about the world
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Beliefs are equal when they agree on everything but their timestamps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Belief {
     key: String,
-    value: String,
+    value: Value,
     certainty: f64,
+    #[serde(default)]
+    source: Source,
+    #[serde(default = "SystemTime::now")]
+    created_at: SystemTime,
+    #[serde(default = "SystemTime::now")]
+    updated_at: SystemTime,
+    #[serde(default)]
+    evidence: Vec<String>,
+    /// Reliability of the source, applied when the certainty is read
+    #[serde(default = "full_reliability")]
+    reliability: f64,
+}
+
+fn full_reliability() -> f64 {
+    1.0
 }
 
 impl Belief {
     /// Create a new belief
-    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
-        Self {
-            key: key.into(),
-            value: value.into(),
-            certainty: 1.0,
-        }
+    pub fn new(key: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self::with_certainty(key, value, 1.0)
     }
 
     /// Create a belief with certainty
-    pub fn with_certainty(
-        key: impl Into<String>,
-        value: impl Into<String>,
-        certainty: f64,
-    ) -> Self {
+    pub fn with_certainty(key: impl Into<String>, value: impl Into<Value>, certainty: f64) -> Self {
+        let now = SystemTime::now();
         Self {
             key: key.into(),
             value: value.into(),
             certainty: certainty.clamp(0.0, 1.0),
+            source: Source::Unknown,
+            created_at: now,
+            updated_at: now,
+            evidence: Vec::new(),
+            reliability: 1.0,
         }
     }
 
+    /// Set the source
+    pub fn with_source(mut self, source: Source) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Add a piece of supporting evidence
+    pub fn with_evidence(mut self, evidence: impl Into<String>) -> Self {
+        self.evidence.push(evidence.into());
+        self
+    }
+
+    /// Set when the belief was formed (both timestamps)
+    pub fn with_timestamp(mut self, time: SystemTime) -> Self {
+        self.created_at = time;
+        self.updated_at = time;
+        self
+    }
+
     /// Create a fact (certainty = 1.0)
-    pub fn fact(key: impl Into<String>, value: impl Into<String>) -> Self {
+    pub fn fact(key: impl Into<String>, value: impl Into<Value>) -> Self {
         Self::new(key, value)
     }
 
@@ -42,17 +107,86 @@ impl Belief {
     }
 
     /// Get the value
-    pub fn value(&self) -> &str {
+    pub fn value(&self) -> &Value {
         &self.value
     }
 
-    /// Get the certainty
+    /// Get the value as a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        self.value.as_bool()
+    }
+
+    /// Get the value as an integer
+    pub fn as_i64(&self) -> Option<i64> {
+        self.value.as_i64()
+    }
+
+    /// Get the value as a float
+    pub fn as_f64(&self) -> Option<f64> {
+        self.value.as_f64()
+    }
+
+    /// Get the value as text, if it is a string or symbol
+    pub fn as_str(&self) -> Option<&str> {
+        self.value.as_str()
+    }
+
+    /// Get the certainty, weighted by the reliability of its source
     pub fn certainty(&self) -> f64 {
+        self.certainty * self.reliability
+    }
+
+    /// Get the certainty as reported, before source reliability applies
+    pub fn reported_certainty(&self) -> f64 {
         self.certainty
     }
 
-    /// Set the certainty
+    /// Set the certainty as reported, before source reliability applies
     pub fn set_certainty(&mut self, certainty: f64) {
         self.certainty = certainty.clamp(0.0, 1.0);
     }
+
+    /// Get the source
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Get when the belief was first formed
+    pub fn created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    /// Get when the belief was last confirmed
+    pub fn updated_at(&self) -> SystemTime {
+        self.updated_at
+    }
+
+    /// Time since the belief was last confirmed
+    pub fn age(&self) -> Duration {
+        self.updated_at.elapsed().unwrap_or_default()
+    }
+
+    /// Get the supporting evidence
+    pub fn evidence(&self) -> &[String] {
+        &self.evidence
+    }
+
+    pub(crate) fn set_created_at(&mut self, time: SystemTime) {
+        self.created_at = time;
+    }
+
+    pub(crate) fn set_reliability(&mut self, reliability: f64) {
+        self.reliability = reliability;
+    }
+}
+
+impl PartialEq for Belief {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self.value == other.value
+            && self.certainty == other.certainty
+            && self.reliability == other.reliability
+            && self.source == other.source
+            && self.evidence == other.evidence
+    }
 }
@@ -1,10 +1,22 @@
-use super::Belief;
+use super::{Belief, Constraint, Entrenchment, RevisionReport, Source, Value};
+use crate::CognitionError;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Collection of beliefs
+///
+/// `add` overwrites without checks; `revise` also retracts whatever the
+/// integrity constraints say conflicts with the new belief.
 #[derive(Debug, Clone)]
 pub struct BeliefBase {
     beliefs: HashMap<String, Belief>,
+    constraints: Vec<Constraint>,
+    entrenchment: Entrenchment,
+    /// When each belief was last added, for recency
+    stamps: HashMap<String, u64>,
+    clock: u64,
+    reliabilities: HashMap<Source, f64>,
 }
 
 impl BeliefBase {
@@ -12,11 +24,64 @@ impl BeliefBase {
     pub fn new() -> Self {
         Self {
             beliefs: HashMap::new(),
+            constraints: Vec::new(),
+            entrenchment: Entrenchment::default(),
+            stamps: HashMap::new(),
+            clock: 0,
+            reliabilities: HashMap::new(),
         }
     }
 
+    /// Add an integrity constraint (existing beliefs are not checked)
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Set which beliefs give way first when consolidating
+    pub fn with_entrenchment(mut self, entrenchment: Entrenchment) -> Self {
+        self.entrenchment = entrenchment;
+        self
+    }
+
+    /// Set how far beliefs from a source are trusted (default 1.0)
+    pub fn with_reliability(mut self, source: Source, reliability: f64) -> Self {
+        self.set_reliability(source, reliability);
+        self
+    }
+
+    /// Set how far beliefs from a source are trusted, including those
+    /// already held
+    pub fn set_reliability(&mut self, source: Source, reliability: f64) {
+        let reliability = reliability.clamp(0.0, 1.0);
+        for belief in self.beliefs.values_mut() {
+            if belief.source() == &source {
+                belief.set_reliability(reliability);
+            }
+        }
+        self.reliabilities.insert(source, reliability);
+    }
+
+    /// Get how far beliefs from a source are trusted
+    pub fn reliability(&self, source: &Source) -> f64 {
+        self.reliabilities.get(source).copied().unwrap_or(1.0)
+    }
+
     /// Add or update a belief
-    pub fn add(&mut self, belief: Belief) {
+    ///
+    /// Its certainty is scaled by the reliability of its source; the
+    /// reported certainty is kept, so adding a belief again does not scale
+    /// it twice. Confirming the value already believed keeps the original
+    /// creation time.
+    pub fn add(&mut self, mut belief: Belief) {
+        belief.set_reliability(self.reliability(belief.source()));
+        if let Some(old) = self.beliefs.get(belief.key()) {
+            if old.value() == belief.value() && old.created_at() < belief.created_at() {
+                belief.set_created_at(old.created_at());
+            }
+        }
+        self.clock += 1;
+        self.stamps.insert(belief.key().to_string(), self.clock);
         self.beliefs.insert(belief.key().to_string(), belief);
     }
 
@@ -25,8 +90,34 @@ impl BeliefBase {
         self.beliefs.get(key)
     }
 
+    /// Get the value of a belief
+    pub fn value(&self, key: &str) -> Option<&Value> {
+        self.get(key).map(Belief::value)
+    }
+
+    /// Get a belief's value as a boolean
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool()
+    }
+
+    /// Get a belief's value as an integer
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key)?.as_i64()
+    }
+
+    /// Get a belief's value as a float
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key)?.as_f64()
+    }
+
+    /// Get a belief's value as text, if it is a string or symbol
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+
     /// Remove a belief
     pub fn remove(&mut self, key: &str) -> Option<Belief> {
+        self.stamps.remove(key);
         self.beliefs.remove(key)
     }
 
@@ -48,9 +139,25 @@ impl BeliefBase {
         self.beliefs.values().filter(|b| predicate(b)).collect()
     }
 
-    /// Clear all beliefs
+    /// Beliefs from a source
+    pub fn from_source(&self, source: &Source) -> Vec<&Belief> {
+        self.query(|b| b.source() == source)
+    }
+
+    /// Beliefs confirmed within the given time
+    pub fn newer_than(&self, age: Duration) -> Vec<&Belief> {
+        self.query(|b| b.age() < age)
+    }
+
+    /// Beliefs not confirmed for at least the given time
+    pub fn older_than(&self, age: Duration) -> Vec<&Belief> {
+        self.query(|b| b.age() >= age)
+    }
+
+    /// Clear all beliefs (constraints are kept)
     pub fn clear(&mut self) {
         self.beliefs.clear();
+        self.stamps.clear();
     }
 
     /// Number of beliefs
@@ -62,6 +169,135 @@ impl BeliefBase {
     pub fn is_empty(&self) -> bool {
         self.beliefs.is_empty()
     }
+
+    /// Get the integrity constraints
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Add an integrity constraint, retracting the least entrenched
+    /// beliefs that violate it
+    pub fn add_constraint(&mut self, constraint: Constraint) -> RevisionReport {
+        self.constraints.push(constraint);
+        self.consolidate()
+    }
+
+    /// Get the entrenchment ordering
+    pub fn entrenchment(&self) -> Entrenchment {
+        self.entrenchment
+    }
+
+    /// Constraints currently violated
+    pub fn violations(&self) -> Vec<&Constraint> {
+        self.constraints
+            .iter()
+            .filter(|c| self.beliefs.values().map(|b| c.matches(b)).sum::<usize>() > 1)
+            .collect()
+    }
+
+    /// Check if no constraint is violated
+    pub fn is_consistent(&self) -> bool {
+        self.violations().is_empty()
+    }
+
+    /// Add a belief without restoring consistency
+    pub fn expand(&mut self, belief: Belief) {
+        self.add(belief);
+    }
+
+    /// Give up a belief
+    pub fn contract(&mut self, key: &str) -> RevisionReport {
+        let mut report = RevisionReport::default();
+        if let Some(belief) = self.remove(key) {
+            report.retract(belief, None);
+        }
+        report
+    }
+
+    /// Accept a belief, first retracting every belief that would conflict
+    /// with it under the constraints
+    ///
+    /// A different value already believed for the same key is replaced and
+    /// reported as retracted. Fails without changing anything if the belief
+    /// violates a constraint on its own.
+    pub fn revise(&mut self, belief: Belief) -> Result<RevisionReport, CognitionError> {
+        if let Some(constraint) = self.constraints.iter().find(|c| c.matches(&belief) > 1) {
+            return Err(CognitionError::BeliefRevisionFailed(format!(
+                "belief {} = {} violates constraint '{}' on its own",
+                belief.key(),
+                belief.value(),
+                constraint.name()
+            )));
+        }
+        let mut report = RevisionReport::default();
+        if let Some(old) = self.get(belief.key()) {
+            if old.value() != belief.value() {
+                report.retract(old.clone(), None);
+            }
+        }
+        for constraint in self.constraints.iter().filter(|c| c.matches(&belief) > 0) {
+            let mut conflicting: Vec<&Belief> = self
+                .beliefs
+                .values()
+                .filter(|b| b.key() != belief.key() && constraint.matches(b) > 0)
+                .collect();
+            conflicting.sort_by(|a, b| a.key().cmp(b.key()));
+            for old in conflicting {
+                if !report.was_retracted(old.key()) {
+                    report.retract(old.clone(), Some(constraint.name()));
+                }
+            }
+        }
+        for retracted in report.retracted() {
+            if retracted.key() != belief.key() {
+                self.remove(retracted.key());
+            }
+        }
+        self.add(belief);
+        Ok(report)
+    }
+
+    /// Restore consistency by retracting the least entrenched beliefs of
+    /// every violated constraint
+    pub fn consolidate(&mut self) -> RevisionReport {
+        let mut report = RevisionReport::default();
+        for constraint in &self.constraints {
+            let mut involved: Vec<&Belief> = self
+                .beliefs
+                .values()
+                .filter(|b| constraint.matches(b) > 0)
+                .collect();
+            involved.sort_by(|a, b| self.compare_entrenchment(b, a));
+            let mut met = 0;
+            let mut retracted = Vec::new();
+            for belief in involved {
+                let matches = constraint.matches(belief);
+                if met + matches > 1 {
+                    retracted.push(belief.key().to_string());
+                } else {
+                    met += matches;
+                }
+            }
+            for key in retracted {
+                if let Some(belief) = self.beliefs.remove(&key) {
+                    self.stamps.remove(&key);
+                    report.retract(belief, Some(constraint.name()));
+                }
+            }
+        }
+        report
+    }
+
+    /// Order beliefs from least to most entrenched
+    fn compare_entrenchment(&self, a: &Belief, b: &Belief) -> Ordering {
+        let certainty = a.certainty().total_cmp(&b.certainty());
+        let recency = self.stamps.get(a.key()).cmp(&self.stamps.get(b.key()));
+        match self.entrenchment {
+            Entrenchment::Certainty => certainty.then(recency),
+            Entrenchment::Recency => recency.then(certainty),
+        }
+        .then_with(|| b.key().cmp(a.key()))
+    }
 }
 
 impl Default for BeliefBase {
@@ -1,5 +1,7 @@
-use super::Goal;
+use super::{BeliefBase, Goal};
 use crate::planning::Plan;
+use crate::reasoning::Monitor;
+use crate::CognitionError;
 
 /// An intention is a commitment to execute a plan to achieve a goal
 #[derive(Debug, Clone)]
@@ -7,6 +9,7 @@ pub struct Intention {
     goal: Goal,
     plan: Plan,
     current_step: usize,
+    guards: Vec<Monitor>,
 }
 
 impl Intention {
@@ -16,9 +19,16 @@ impl Intention {
             goal,
             plan,
             current_step: 0,
+            guards: Vec::new(),
         }
     }
 
+    /// Add a temporal property that must hold while the plan executes
+    pub fn with_guard(mut self, guard: Monitor) -> Self {
+        self.guards.push(guard);
+        self
+    }
+
     /// Get the goal
     pub fn goal(&self) -> &Goal {
         &self.goal
@@ -29,6 +39,11 @@ impl Intention {
         &self.plan
     }
 
+    /// Get the guards
+    pub fn guards(&self) -> &[Monitor] {
+        &self.guards
+    }
+
     /// Get current step
     pub fn current_step(&self) -> usize {
         self.current_step
@@ -41,6 +56,20 @@ impl Intention {
         }
     }
 
+    /// Check the guards against a belief snapshot, then advance
+    ///
+    /// The step is not taken if a guard has been violated.
+    pub fn advance(&mut self, beliefs: &BeliefBase) -> Result<(), CognitionError> {
+        for guard in &mut self.guards {
+            guard.observe(beliefs);
+            if let Some(violation) = guard.violation() {
+                return Err(CognitionError::PropertyViolated(violation.clone()));
+            }
+        }
+        self.next_step();
+        Ok(())
+    }
+
     /// Check if completed
     pub fn is_completed(&self) -> bool {
         self.current_step >= self.plan.actions().len()
@@ -49,5 +78,6 @@ impl Intention {
     /// Reset to beginning
     pub fn reset(&mut self) {
         self.current_step = 0;
+        self.guards.iter_mut().for_each(Monitor::reset);
     }
 }
@@ -7,11 +7,15 @@ pub mod desire;
 pub mod goal;
 pub mod intention;
 pub mod intention_stack;
+pub mod revision;
+pub mod value;
 
 pub use bdi_agent::BDIAgent;
-pub use belief::Belief;
+pub use belief::{Belief, Source};
 pub use belief_base::BeliefBase;
 pub use desire::Desire;
 pub use goal::{Goal, GoalType};
 pub use intention::Intention;
 pub use intention_stack::IntentionStack;
+pub use revision::{Constraint, Entrenchment, RevisionReport};
+pub use value::Value;
@@ -0,0 +1,102 @@
+use super::{Belief, Value};
+use serde::{Deserialize, Serialize};
+
+/// Integrity constraint: at most one of its conditions may hold
+///
+/// A condition is either a key being believed at all or a key being
+/// believed with a particular value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Constraint {
+    name: String,
+    conditions: Vec<(String, Option<Value>)>,
+}
+
+impl Constraint {
+    /// Create a constraint with no conditions
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Add the condition that a key is believed
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.conditions.push((key.into(), None));
+        self
+    }
+
+    /// Add the condition that a key is believed with a value
+    pub fn with_value(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.conditions.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the conditions
+    pub fn conditions(&self) -> &[(String, Option<Value>)] {
+        &self.conditions
+    }
+
+    /// Number of conditions a belief meets
+    pub fn matches(&self, belief: &Belief) -> usize {
+        self.conditions
+            .iter()
+            .filter(|(key, value)| {
+                key == belief.key() && value.as_ref().is_none_or(|v| v == belief.value())
+            })
+            .count()
+    }
+}
+
+/// Which beliefs give way first when consistency must be restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Entrenchment {
+    /// Less certain beliefs go first, older ones breaking ties
+    #[default]
+    Certainty,
+    /// Older beliefs go first, less certain ones breaking ties
+    Recency,
+}
+
+/// Outcome of a revision operation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RevisionReport {
+    retracted: Vec<Belief>,
+    violated: Vec<String>,
+}
+
+impl RevisionReport {
+    pub(crate) fn retract(&mut self, belief: Belief, constraint: Option<&str>) {
+        self.retracted.push(belief);
+        if let Some(name) = constraint {
+            if !self.violated.iter().any(|v| v == name) {
+                self.violated.push(name.to_string());
+            }
+        }
+    }
+
+    /// Get the beliefs retracted, in order
+    pub fn retracted(&self) -> &[Belief] {
+        &self.retracted
+    }
+
+    /// Get the names of the constraints that forced retractions
+    pub fn violated(&self) -> &[String] {
+        &self.violated
+    }
+
+    /// Check if a belief was retracted
+    pub fn was_retracted(&self, key: &str) -> bool {
+        self.retracted.iter().any(|b| b.key() == key)
+    }
+
+    /// Check if nothing was retracted
+    pub fn is_empty(&self) -> bool {
+        self.retracted.is_empty()
+    }
+}
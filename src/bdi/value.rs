@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Typed value of a belief
+///
+/// Integers and floats compare numerically with each other; other values
+/// only compare with values of the same kind. Text converted into a value
+/// becomes a number or boolean when it reads back unchanged, so
+/// `Value::from("80")` is the integer 80; anything else stays a string.
+/// Bare strings stored before values were typed deserialize the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "Stored")]
+pub enum Value {
+    /// Boolean
+    Bool(bool),
+    /// Whole number
+    Integer(i64),
+    /// Floating point number
+    Float(f64),
+    /// Free text
+    String(String),
+    /// Ordered list of values
+    List(Vec<Value>),
+    /// Values by name
+    Map(BTreeMap<String, Value>),
+    /// Symbolic constant such as `idle`
+    Symbol(String),
+}
+
+/// Serialized forms a value is read from
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    /// Text written before values were typed
+    Text(String),
+    Typed(Typed),
+}
+
+/// `Value` as written by its `Serialize`
+#[derive(Deserialize)]
+enum Typed {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+    Symbol(String),
+}
+
+impl From<Stored> for Value {
+    fn from(stored: Stored) -> Self {
+        match stored {
+            Stored::Text(text) => Value::from(text),
+            Stored::Typed(Typed::Bool(b)) => Value::Bool(b),
+            Stored::Typed(Typed::Integer(n)) => Value::Integer(n),
+            Stored::Typed(Typed::Float(x)) => Value::Float(x),
+            Stored::Typed(Typed::String(s)) => Value::String(s),
+            Stored::Typed(Typed::List(items)) => Value::List(items),
+            Stored::Typed(Typed::Map(entries)) => Value::Map(entries),
+            Stored::Typed(Typed::Symbol(name)) => Value::Symbol(name),
+        }
+    }
+}
+
+impl Value {
+    /// Create a symbol
+    pub fn symbol(name: impl Into<String>) -> Self {
+        Value::Symbol(name.into())
+    }
+
+    /// Get the value as a boolean, parsing `"true"` and `"false"`
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) | Value::Symbol(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an integer, parsing strings
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a float, converting integers and parsing strings
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(n) => Some(*n as f64),
+            Value::Float(x) => Some(*x),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the text of a string or symbol
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) | Value::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of a list
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Get the entries of a map
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::String(s) | Value::Symbol(s) => write!(f, "{}", s),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) | (Value::Symbol(a), Value::Symbol(b)) => {
+                a.partial_cmp(b)
+            }
+            (Value::List(a), Value::List(b)) => a.partial_cmp(b),
+            (Value::Map(a), Value::Map(b)) => (a == b).then_some(Ordering::Equal),
+            (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => {
+                self.as_f64()?.partial_cmp(&other.as_f64()?)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Text comparison, so `belief.value() == "25"` holds for any value shown as `25`
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Value::String(s) | Value::Symbol(s) => s == other,
+            _ => {
+                let text = self.to_string();
+                text == other
+            }
+        }
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+macro_rules! compare_with {
+    ($($t:ty),*) => {
+        $(
+            impl PartialEq<$t> for Value {
+                fn eq(&self, other: &$t) -> bool {
+                    self.partial_cmp(other) == Some(Ordering::Equal)
+                }
+            }
+
+            impl PartialOrd<$t> for Value {
+                fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                    self.partial_cmp(&Value::from(*other))
+                }
+            }
+
+            impl PartialEq<$t> for &Value {
+                fn eq(&self, other: &$t) -> bool {
+                    **self == *other
+                }
+            }
+
+            impl PartialOrd<$t> for &Value {
+                fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                    (**self).partial_cmp(other)
+                }
+            }
+        )*
+    };
+}
+
+compare_with!(bool, i32, i64, f64);
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Integer(n.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Integer(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Float(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::from(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    /// `true`, `false` and numbers that print back as the same text become
+    /// typed values; text such as `007` or `1.50` stays a string
+    fn from(s: String) -> Self {
+        if let Ok(b) = s.parse() {
+            return Value::Bool(b);
+        }
+        if let Ok(n) = s.parse::<i64>() {
+            if n.to_string() == s {
+                return Value::Integer(n);
+            }
+        }
+        match s.parse::<f64>() {
+            Ok(x) if x.is_finite() && x.to_string() == s => Value::Float(x),
+            _ => Value::String(s),
+        }
+    }
+}
+
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Value::from(s.clone())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::List(items)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(entries: BTreeMap<String, Value>) -> Self {
+        Value::Map(entries)
+    }
+}
@@ -1,5 +1,9 @@
 ﻿//! Decision-making module
 
+pub mod tree;
 pub mod utility;
+pub mod voting;
 
+pub use tree::{DecisionNode, DecisionTree, Parameter};
 pub use utility::UtilityFunction;
+pub use voting::{Ballot, Election, TieBreak, VoteOutcome, VotingRule};
@@ -0,0 +1,512 @@
+use crate::explanation::{Explanation, Reason};
+use crate::CognitionError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Tolerance when checking that chance probabilities sum to one
+const PROBABILITY_EPSILON: f64 = 1e-9;
+
+/// Kind of decision tree node
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NodeKind {
+    /// The agent picks the branch with the highest expected value
+    Decision,
+    /// Nature picks a branch according to its probability
+    Chance,
+    /// Leaf with a fixed payoff
+    Terminal(f64),
+}
+
+/// Edge from a node to one of its children
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Branch {
+    label: String,
+    probability: f64,
+    node: DecisionNode,
+}
+
+impl Branch {
+    /// Get the label
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Get the probability (1.0 for decision options)
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Get the child node
+    pub fn node(&self) -> &DecisionNode {
+        &self.node
+    }
+}
+
+/// A node in a decision tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionNode {
+    name: String,
+    kind: NodeKind,
+    branches: Vec<Branch>,
+}
+
+impl DecisionNode {
+    /// Create a decision node
+    pub fn decision(name: impl Into<String>) -> Self {
+        Self::with_kind(name, NodeKind::Decision)
+    }
+
+    /// Create a chance node
+    pub fn chance(name: impl Into<String>) -> Self {
+        Self::with_kind(name, NodeKind::Chance)
+    }
+
+    /// Create a terminal node
+    pub fn terminal(name: impl Into<String>, payoff: f64) -> Self {
+        Self::with_kind(name, NodeKind::Terminal(payoff))
+    }
+
+    fn with_kind(name: impl Into<String>, kind: NodeKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            branches: Vec::new(),
+        }
+    }
+
+    /// Add an option to a decision node
+    pub fn with_option(mut self, label: impl Into<String>, node: DecisionNode) -> Self {
+        self.branches.push(Branch {
+            label: label.into(),
+            probability: 1.0,
+            node,
+        });
+        self
+    }
+
+    /// Add an outcome to a chance node
+    pub fn with_outcome(
+        mut self,
+        label: impl Into<String>,
+        probability: f64,
+        node: DecisionNode,
+    ) -> Self {
+        self.branches.push(Branch {
+            label: label.into(),
+            probability,
+            node,
+        });
+        self
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the kind
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Get branches
+    pub fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    /// Check structure and probabilities of this subtree
+    fn validate(&self) -> Result<(), CognitionError> {
+        match self.kind {
+            NodeKind::Terminal(_) if !self.branches.is_empty() => {
+                Err(invalid(&self.name, "terminal nodes cannot have branches"))
+            }
+            NodeKind::Decision | NodeKind::Chance if self.branches.is_empty() => {
+                Err(invalid(&self.name, "node has no branches"))
+            }
+            NodeKind::Chance => {
+                if self
+                    .branches
+                    .iter()
+                    .any(|b| !(0.0..=1.0).contains(&b.probability))
+                {
+                    return Err(invalid(&self.name, "probabilities must be in [0, 1]"));
+                }
+                let total: f64 = self.branches.iter().map(|b| b.probability).sum();
+                if (total - 1.0).abs() > PROBABILITY_EPSILON {
+                    return Err(invalid(
+                        &self.name,
+                        &format!("probabilities sum to {}, expected 1", total),
+                    ));
+                }
+                self.validate_children()
+            }
+            _ => self.validate_children(),
+        }
+    }
+
+    fn validate_children(&self) -> Result<(), CognitionError> {
+        self.branches.iter().try_for_each(|b| b.node.validate())
+    }
+
+    /// Roll back expected values from the leaves
+    fn rollback(&self) -> f64 {
+        match self.kind {
+            NodeKind::Terminal(payoff) => payoff,
+            NodeKind::Chance => self
+                .branches
+                .iter()
+                .map(|b| b.probability * b.node.rollback())
+                .sum(),
+            NodeKind::Decision => self
+                .branches
+                .iter()
+                .map(|b| b.node.rollback())
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    /// Collect the optimal choice at every reachable decision node
+    fn collect_choices(&self, choices: &mut Vec<Choice>) {
+        if self.kind == NodeKind::Decision {
+            let alternatives: Vec<(String, f64)> = self
+                .branches
+                .iter()
+                .map(|b| (b.label.clone(), b.node.rollback()))
+                .collect();
+            let best = best_index(&alternatives);
+            choices.push(Choice {
+                decision: self.name.clone(),
+                option: alternatives[best].0.clone(),
+                value: alternatives[best].1,
+                alternatives,
+            });
+        }
+        for branch in &self.branches {
+            branch.node.collect_choices(choices);
+        }
+    }
+
+    /// Every chance node with the given name, depth first
+    fn find_chances<'a>(&'a self, name: &str, found: &mut Vec<&'a DecisionNode>) {
+        if self.kind == NodeKind::Chance && self.name == name {
+            found.push(self);
+        }
+        for branch in &self.branches {
+            branch.node.find_chances(name, found);
+        }
+    }
+
+    /// Replace every chance node `name` with the subtree of outcome `label`
+    fn resolve(&self, name: &str, label: &str) -> DecisionNode {
+        if self.kind == NodeKind::Chance && self.name == name {
+            if let Some(branch) = self.branches.iter().find(|b| b.label == label) {
+                return branch.node.resolve(name, label);
+            }
+        }
+        let mut node = self.clone();
+        for branch in &mut node.branches {
+            branch.node = branch.node.resolve(name, label);
+        }
+        node
+    }
+
+    /// Apply a parameter value to every matching node; returns matches
+    fn apply(&mut self, parameter: &Parameter, value: f64) -> usize {
+        let mut matched = 0;
+        match parameter {
+            Parameter::Payoff { terminal } => {
+                if let NodeKind::Terminal(payoff) = &mut self.kind {
+                    if self.name == *terminal {
+                        *payoff = value;
+                        matched += 1;
+                    }
+                }
+            }
+            Parameter::Probability { node, outcome } => {
+                if self.kind == NodeKind::Chance && self.name == *node {
+                    if let Some(target) = self.branches.iter().position(|b| b.label == *outcome) {
+                        rescale(&mut self.branches, target, value);
+                        matched += 1;
+                    }
+                }
+            }
+        }
+        for branch in &mut self.branches {
+            matched += branch.node.apply(parameter, value);
+        }
+        matched
+    }
+
+    fn render(&self, label: Option<&str>, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        let edge = label.map(|l| format!("[{}] ", l)).unwrap_or_default();
+        let kind = match self.kind {
+            NodeKind::Decision => "decision",
+            NodeKind::Chance => "chance",
+            NodeKind::Terminal(_) => "terminal",
+        };
+        writeln!(
+            f,
+            "{}{}{} ({}) EV={:.4}",
+            indent,
+            edge,
+            self.name,
+            kind,
+            self.rollback()
+        )?;
+        for branch in &self.branches {
+            let label = match self.kind {
+                NodeKind::Chance => format!("{} p={}", branch.label, branch.probability),
+                _ => branch.label.clone(),
+            };
+            branch.node.render(Some(&label), depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Optimal option at a decision node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Choice {
+    decision: String,
+    option: String,
+    value: f64,
+    alternatives: Vec<(String, f64)>,
+}
+
+impl Choice {
+    /// Get the decision node name
+    pub fn decision(&self) -> &str {
+        &self.decision
+    }
+
+    /// Get the chosen option
+    pub fn option(&self) -> &str {
+        &self.option
+    }
+
+    /// Get the expected value of the chosen option
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Get every option with its expected value
+    pub fn alternatives(&self) -> &[(String, f64)] {
+        &self.alternatives
+    }
+
+    /// Explain the choice by comparing the expected utility of every option
+    pub fn explain(&self) -> Explanation {
+        let root = Explanation::new(
+            format!("{}: {}", self.decision, self.option),
+            Reason::Chosen(self.value),
+        );
+        self.alternatives
+            .iter()
+            .fold(root, |explanation, (option, value)| {
+                let reason = if *option == self.option {
+                    Reason::Chosen(*value)
+                } else {
+                    Reason::Rejected(*value)
+                };
+                explanation.with_child(Explanation::new(option.clone(), reason))
+            })
+    }
+}
+
+/// Tree parameter varied during sensitivity analysis
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parameter {
+    /// Probability of `outcome` at chance node `node`; siblings are rescaled
+    Probability { node: String, outcome: String },
+    /// Payoff of terminal node `terminal`
+    Payoff { terminal: String },
+}
+
+impl Parameter {
+    /// Probability of an outcome at a chance node
+    pub fn probability(node: impl Into<String>, outcome: impl Into<String>) -> Self {
+        Parameter::Probability {
+            node: node.into(),
+            outcome: outcome.into(),
+        }
+    }
+
+    /// Payoff of a terminal node
+    pub fn payoff(terminal: impl Into<String>) -> Self {
+        Parameter::Payoff {
+            terminal: terminal.into(),
+        }
+    }
+}
+
+/// Result of evaluating the tree at one parameter value
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensitivityPoint {
+    value: f64,
+    expected_value: f64,
+    best_option: Option<String>,
+}
+
+impl SensitivityPoint {
+    /// Get the parameter value
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Get the expected value of the tree
+    pub fn expected_value(&self) -> f64 {
+        self.expected_value
+    }
+
+    /// Get the optimal option at the root (if the root is a decision)
+    pub fn best_option(&self) -> Option<&str> {
+        self.best_option.as_deref()
+    }
+}
+
+/// Decision tree with expected value rollback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTree {
+    root: DecisionNode,
+}
+
+impl DecisionTree {
+    /// Create a new decision tree
+    pub fn new(root: DecisionNode) -> Self {
+        Self { root }
+    }
+
+    /// Get the root node
+    pub fn root(&self) -> &DecisionNode {
+        &self.root
+    }
+
+    /// Check that the tree is well formed
+    pub fn validate(&self) -> Result<(), CognitionError> {
+        self.root.validate()
+    }
+
+    /// Expected value of the tree under the optimal policy
+    pub fn expected_value(&self) -> Result<f64, CognitionError> {
+        self.validate()?;
+        Ok(self.root.rollback())
+    }
+
+    /// Optimal choice at every decision node, in depth-first order
+    pub fn policy(&self) -> Result<Vec<Choice>, CognitionError> {
+        self.validate()?;
+        let mut choices = Vec::new();
+        self.root.collect_choices(&mut choices);
+        Ok(choices)
+    }
+
+    /// Expected value gained by learning the outcome of a chance node first
+    ///
+    /// Chance nodes sharing the name are the same uncertainty and must have
+    /// the same outcomes and probabilities.
+    pub fn value_of_perfect_information(&self, chance: &str) -> Result<f64, CognitionError> {
+        let baseline = self.expected_value()?;
+        let mut nodes = Vec::new();
+        self.root.find_chances(chance, &mut nodes);
+        let node = *nodes.first().ok_or_else(|| {
+            CognitionError::DecisionFailed(format!("no chance node named '{}'", chance))
+        })?;
+        let outcomes = |n: &DecisionNode| -> Vec<(String, f64)> {
+            n.branches
+                .iter()
+                .map(|b| (b.label.clone(), b.probability))
+                .collect()
+        };
+        if nodes.iter().any(|n| outcomes(n) != outcomes(node)) {
+            return Err(CognitionError::DecisionFailed(format!(
+                "chance nodes named '{}' have different outcomes",
+                chance
+            )));
+        }
+
+        let informed: f64 = node
+            .branches
+            .iter()
+            .map(|b| b.probability * self.root.resolve(chance, &b.label).rollback())
+            .sum();
+        Ok((informed - baseline).max(0.0))
+    }
+
+    /// One-way sensitivity analysis over a single parameter
+    pub fn sensitivity(
+        &self,
+        parameter: &Parameter,
+        values: &[f64],
+    ) -> Result<Vec<SensitivityPoint>, CognitionError> {
+        self.validate()?;
+        values
+            .iter()
+            .map(|&value| {
+                let mut root = self.root.clone();
+                if root.apply(parameter, value) == 0 {
+                    return Err(CognitionError::DecisionFailed(format!(
+                        "parameter {:?} does not match any node",
+                        parameter
+                    )));
+                }
+                root.validate()?;
+
+                let best_option = (root.kind == NodeKind::Decision).then(|| {
+                    let values: Vec<(String, f64)> = root
+                        .branches
+                        .iter()
+                        .map(|b| (b.label.clone(), b.node.rollback()))
+                        .collect();
+                    values[best_index(&values)].0.clone()
+                });
+                Ok(SensitivityPoint {
+                    value,
+                    expected_value: root.rollback(),
+                    best_option,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for DecisionTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.root.render(None, 0, f)
+    }
+}
+
+/// Index of the highest value; earlier entries win ties
+fn best_index(values: &[(String, f64)]) -> usize {
+    let mut best = 0;
+    for (i, (_, value)) in values.iter().enumerate() {
+        if *value > values[best].1 {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Set one outcome's probability and rescale the others to keep the sum at one
+fn rescale(branches: &mut [Branch], target: usize, probability: f64) {
+    let rest: f64 = branches
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != target)
+        .map(|(_, b)| b.probability)
+        .sum();
+    let others = branches.len() - 1;
+    for (i, branch) in branches.iter_mut().enumerate() {
+        branch.probability = if i == target {
+            probability
+        } else if rest > 0.0 {
+            branch.probability / rest * (1.0 - probability)
+        } else {
+            (1.0 - probability) / others as f64
+        };
+    }
+}
+
+fn invalid(node: &str, reason: &str) -> CognitionError {
+    CognitionError::DecisionFailed(format!("invalid node '{}': {}", node, reason))
+}
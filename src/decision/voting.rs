@@ -0,0 +1,506 @@
+use crate::bdi::{BDIAgent, Desire};
+use crate::CognitionError;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Largest candidate set accepted by the Kemeny rule
+pub const MAX_KEMENY_CANDIDATES: usize = 8;
+
+/// Rule used to aggregate ballots into a group decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VotingRule {
+    /// Candidate with the most first preferences wins
+    Plurality,
+    /// Positional scoring: `n - 1` points for first place down to 0
+    Borda,
+    /// Pairwise majority contests: 1 point per win, 0.5 per draw
+    Copeland,
+    /// Repeatedly eliminate the candidate with the fewest first preferences
+    InstantRunoff,
+    /// Each voter approves their top `k` candidates
+    Approval(usize),
+    /// Ranking with the fewest pairwise disagreements with the ballots
+    Kemeny,
+}
+
+/// How ties between candidates are broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// Earliest candidate in the election's candidate list wins
+    #[default]
+    CandidateOrder,
+    /// Lexicographically smallest candidate name wins
+    Lexicographic,
+    /// Ties are reported as errors
+    Fail,
+}
+
+/// A ranked preference over candidates, most preferred first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ballot {
+    ranking: Vec<String>,
+}
+
+impl Ballot {
+    /// Create a ballot from a ranking
+    pub fn new<I, S>(ranking: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut ballot = Self {
+            ranking: Vec::new(),
+        };
+        for candidate in ranking {
+            let candidate = candidate.into();
+            if !ballot.ranking.contains(&candidate) {
+                ballot.ranking.push(candidate);
+            }
+        }
+        ballot
+    }
+
+    /// Rank goals by desire priority (highest first)
+    pub fn from_desires(desires: &[Desire]) -> Self {
+        let mut sorted: Vec<&Desire> = desires.iter().collect();
+        sorted.sort_by(|a, b| {
+            b.priority()
+                .partial_cmp(&a.priority())
+                .unwrap_or(Ordering::Equal)
+        });
+        Self::new(sorted.into_iter().map(|d| d.goal().name().to_string()))
+    }
+
+    /// Rank an agent's desires
+    pub fn from_agent(agent: &BDIAgent) -> Self {
+        Self::from_desires(agent.desires())
+    }
+
+    /// Get the ranking
+    pub fn ranking(&self) -> &[String] {
+        &self.ranking
+    }
+
+    /// Most preferred candidate among `remaining`
+    fn top<'a>(&'a self, remaining: &[&str]) -> Option<&'a str> {
+        self.ranking
+            .iter()
+            .map(String::as_str)
+            .find(|c| remaining.contains(c))
+    }
+
+    /// Position of a candidate; unranked candidates share the last place
+    fn position(&self, candidate: &str) -> usize {
+        self.ranking
+            .iter()
+            .position(|c| c == candidate)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// Result of running an election
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteOutcome {
+    winner: String,
+    ranking: Vec<String>,
+    scores: HashMap<String, f64>,
+}
+
+impl VoteOutcome {
+    /// Get the winning candidate
+    pub fn winner(&self) -> &str {
+        &self.winner
+    }
+
+    /// Get the full social ranking, winner first
+    pub fn ranking(&self) -> &[String] {
+        &self.ranking
+    }
+
+    /// Get per-candidate scores (empty for instant-runoff and Kemeny)
+    pub fn scores(&self) -> &HashMap<String, f64> {
+        &self.scores
+    }
+}
+
+/// Group decision over a shared set of candidate goals
+#[derive(Debug, Clone)]
+pub struct Election {
+    candidates: Vec<String>,
+    ballots: Vec<Ballot>,
+    tie_break: TieBreak,
+}
+
+impl Election {
+    /// Create a new election over candidates
+    pub fn new<I, S>(candidates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut election = Self {
+            candidates: Vec::new(),
+            ballots: Vec::new(),
+            tie_break: TieBreak::default(),
+        };
+        for candidate in candidates {
+            election.add_candidate(candidate);
+        }
+        election
+    }
+
+    /// Create an election where every agent votes on the goals of its desires
+    pub fn from_agents(agents: &[BDIAgent]) -> Self {
+        let mut election = Self::new(Vec::<String>::new());
+        for agent in agents {
+            let ballot = Ballot::from_agent(agent);
+            for candidate in ballot.ranking() {
+                election.add_candidate(candidate.clone());
+            }
+            election.ballots.push(ballot);
+        }
+        election
+    }
+
+    /// Set the tie-breaking rule
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Add a ballot
+    pub fn with_ballot(mut self, ballot: Ballot) -> Self {
+        self.ballots.push(ballot);
+        self
+    }
+
+    /// Add a ballot
+    pub fn add_ballot(&mut self, ballot: Ballot) {
+        self.ballots.push(ballot);
+    }
+
+    /// Add a candidate (ignored if already present)
+    pub fn add_candidate(&mut self, candidate: impl Into<String>) {
+        let candidate = candidate.into();
+        if !self.candidates.contains(&candidate) {
+            self.candidates.push(candidate);
+        }
+    }
+
+    /// Get candidates
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// Get ballots
+    pub fn ballots(&self) -> &[Ballot] {
+        &self.ballots
+    }
+
+    /// Get the tie-breaking rule
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+
+    /// Run the election with the given rule
+    pub fn run(&self, rule: VotingRule) -> Result<VoteOutcome, CognitionError> {
+        if self.candidates.is_empty() {
+            return Err(CognitionError::DecisionFailed(
+                "election has no candidates".to_string(),
+            ));
+        }
+
+        match rule {
+            VotingRule::Plurality => self.scored(self.plurality_scores()),
+            VotingRule::Borda => self.scored(self.borda_scores()),
+            VotingRule::Copeland => self.scored(self.copeland_scores()),
+            VotingRule::Approval(k) => self.scored(self.approval_scores(k)),
+            VotingRule::InstantRunoff => self.instant_runoff(),
+            VotingRule::Kemeny => self.kemeny(),
+        }
+    }
+
+    /// Number of ballots preferring `a` over `b`, indexed by candidate position
+    pub fn pairwise_matrix(&self) -> Vec<Vec<usize>> {
+        let n = self.candidates.len();
+        let mut matrix = vec![vec![0; n]; n];
+        for ballot in &self.ballots {
+            let positions: Vec<usize> =
+                self.candidates.iter().map(|c| ballot.position(c)).collect();
+            for a in 0..n {
+                for b in 0..n {
+                    if positions[a] < positions[b] {
+                        matrix[a][b] += 1;
+                    }
+                }
+            }
+        }
+        matrix
+    }
+
+    /// Candidate beating every other candidate in pairwise majority contests
+    pub fn condorcet_winner(&self) -> Option<&str> {
+        let matrix = self.pairwise_matrix();
+        let n = self.candidates.len();
+        (0..n)
+            .find(|&a| (0..n).all(|b| a == b || matrix[a][b] > matrix[b][a]))
+            .map(|a| self.candidates[a].as_str())
+    }
+
+    /// Groups of candidates that beat each other in a majority cycle
+    ///
+    /// Each group is a strongly connected component of the strict majority
+    /// graph with more than one member, listed in candidate order.
+    pub fn condorcet_cycles(&self) -> Vec<Vec<String>> {
+        let matrix = self.pairwise_matrix();
+        let n = self.candidates.len();
+
+        // reach[a][b]: a beats b directly or through a chain of majority wins
+        let mut reach: Vec<Vec<bool>> = (0..n)
+            .map(|a| (0..n).map(|b| matrix[a][b] > matrix[b][a]).collect())
+            .collect();
+        for k in 0..n {
+            let via = reach[k].clone();
+            for row in reach.iter_mut().filter(|row| row[k]) {
+                for (cell, &through) in row.iter_mut().zip(&via) {
+                    *cell |= through;
+                }
+            }
+        }
+
+        let mut assigned = vec![false; n];
+        let mut cycles = Vec::new();
+        for a in 0..n {
+            if assigned[a] || !reach[a][a] {
+                continue;
+            }
+            let members: Vec<usize> = (0..n).filter(|&b| reach[a][b] && reach[b][a]).collect();
+            for &m in &members {
+                assigned[m] = true;
+            }
+            cycles.push(
+                members
+                    .into_iter()
+                    .map(|m| self.candidates[m].clone())
+                    .collect(),
+            );
+        }
+        cycles
+    }
+
+    fn plurality_scores(&self) -> Vec<f64> {
+        self.approval_scores(1)
+    }
+
+    fn approval_scores(&self, k: usize) -> Vec<f64> {
+        let mut scores = vec![0.0; self.candidates.len()];
+        for ballot in &self.ballots {
+            let approved = ballot
+                .ranking
+                .iter()
+                .filter_map(|c| self.index_of(c))
+                .take(k);
+            for i in approved {
+                scores[i] += 1.0;
+            }
+        }
+        scores
+    }
+
+    fn borda_scores(&self) -> Vec<f64> {
+        let n = self.candidates.len();
+        let mut scores = vec![0.0; n];
+        for ballot in &self.ballots {
+            let ranked = ballot.ranking.iter().filter_map(|c| self.index_of(c));
+            for (place, i) in ranked.enumerate() {
+                scores[i] += (n - 1 - place) as f64;
+            }
+        }
+        scores
+    }
+
+    fn copeland_scores(&self) -> Vec<f64> {
+        let matrix = self.pairwise_matrix();
+        let n = self.candidates.len();
+        (0..n)
+            .map(|a| {
+                (0..n)
+                    .filter(|&b| b != a)
+                    .map(|b| match matrix[a][b].cmp(&matrix[b][a]) {
+                        Ordering::Greater => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Less => 0.0,
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn scored(&self, scores: Vec<f64>) -> Result<VoteOutcome, CognitionError> {
+        let mut order: Vec<usize> = (0..self.candidates.len()).collect();
+        order.sort_by(|&a, &b| {
+            scores[b]
+                .partial_cmp(&scores[a])
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| self.tie_order(a, b))
+        });
+
+        if self.tie_break == TieBreak::Fail && order.len() > 1 {
+            let tied: Vec<&str> = order
+                .iter()
+                .filter(|&&i| scores[i] == scores[order[0]])
+                .map(|&i| self.candidates[i].as_str())
+                .collect();
+            if tied.len() > 1 {
+                return Err(self.tie_error(&tied));
+            }
+        }
+
+        let ranking: Vec<String> = order.iter().map(|&i| self.candidates[i].clone()).collect();
+        Ok(VoteOutcome {
+            winner: ranking[0].clone(),
+            ranking,
+            scores: self.candidates.iter().cloned().zip(scores).collect(),
+        })
+    }
+
+    fn instant_runoff(&self) -> Result<VoteOutcome, CognitionError> {
+        let mut remaining: Vec<usize> = (0..self.candidates.len()).collect();
+        let mut eliminated = Vec::new();
+
+        while remaining.len() > 1 {
+            let names: Vec<&str> = remaining
+                .iter()
+                .map(|&i| self.candidates[i].as_str())
+                .collect();
+            let mut counts = vec![0usize; remaining.len()];
+            for ballot in &self.ballots {
+                if let Some(top) = ballot.top(&names) {
+                    if let Some(slot) = names.iter().position(|&c| c == top) {
+                        counts[slot] += 1;
+                    }
+                }
+            }
+
+            let fewest = counts.iter().copied().min().unwrap_or(0);
+            let losers: Vec<usize> = (0..remaining.len())
+                .filter(|&s| counts[s] == fewest)
+                .map(|s| remaining[s])
+                .collect();
+            if losers.len() > 1 && self.tie_break == TieBreak::Fail {
+                let tied: Vec<&str> = losers
+                    .iter()
+                    .map(|&i| self.candidates[i].as_str())
+                    .collect();
+                return Err(self.tie_error(&tied));
+            }
+            let loser = losers
+                .into_iter()
+                .max_by(|&a, &b| self.tie_order(a, b))
+                .unwrap_or(remaining[0]);
+            remaining.retain(|&i| i != loser);
+            eliminated.push(loser);
+        }
+
+        let ranking: Vec<String> = remaining
+            .into_iter()
+            .chain(eliminated.into_iter().rev())
+            .map(|i| self.candidates[i].clone())
+            .collect();
+        Ok(VoteOutcome {
+            winner: ranking[0].clone(),
+            ranking,
+            scores: HashMap::new(),
+        })
+    }
+
+    fn kemeny(&self) -> Result<VoteOutcome, CognitionError> {
+        let n = self.candidates.len();
+        if n > MAX_KEMENY_CANDIDATES {
+            return Err(CognitionError::DecisionFailed(format!(
+                "Kemeny rule supports at most {} candidates, got {}",
+                MAX_KEMENY_CANDIDATES, n
+            )));
+        }
+
+        let matrix = self.pairwise_matrix();
+        let mut start: Vec<usize> = (0..n).collect();
+        start.sort_by(|&a, &b| self.tie_order(a, b));
+
+        // Enumerate permutations in tie-break order; keep the first optimum
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        let mut optimal_winners = Vec::new();
+        for_each_permutation(&mut start, 0, &mut |perm| {
+            let mut agreement = 0;
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    agreement += matrix[perm[i]][perm[j]];
+                }
+            }
+            match &best {
+                Some((score, _)) if agreement < *score => {}
+                Some((score, _)) if agreement == *score => {
+                    if !optimal_winners.contains(&perm[0]) {
+                        optimal_winners.push(perm[0]);
+                    }
+                }
+                _ => {
+                    best = Some((agreement, perm.to_vec()));
+                    optimal_winners = vec![perm[0]];
+                }
+            }
+        });
+
+        if optimal_winners.len() > 1 && self.tie_break == TieBreak::Fail {
+            let tied: Vec<&str> = optimal_winners
+                .iter()
+                .map(|&i| self.candidates[i].as_str())
+                .collect();
+            return Err(self.tie_error(&tied));
+        }
+
+        let ranking: Vec<String> = best
+            .map(|(_, perm)| perm)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| self.candidates[i].clone())
+            .collect();
+        Ok(VoteOutcome {
+            winner: ranking[0].clone(),
+            ranking,
+            scores: HashMap::new(),
+        })
+    }
+
+    fn index_of(&self, candidate: &str) -> Option<usize> {
+        self.candidates.iter().position(|c| c == candidate)
+    }
+
+    /// Ordering of two candidates under the tie-breaking rule (preferred first)
+    fn tie_order(&self, a: usize, b: usize) -> Ordering {
+        match self.tie_break {
+            TieBreak::Lexicographic => self.candidates[a].cmp(&self.candidates[b]),
+            TieBreak::CandidateOrder | TieBreak::Fail => a.cmp(&b),
+        }
+    }
+
+    fn tie_error(&self, tied: &[&str]) -> CognitionError {
+        CognitionError::DecisionFailed(format!("unresolved tie between {}", tied.join(", ")))
+    }
+}
+
+/// Visit every permutation of `items[k..]` in lexicographic order of positions
+fn for_each_permutation<F>(items: &mut [usize], k: usize, visit: &mut F)
+where
+    F: FnMut(&[usize]),
+{
+    if k == items.len() {
+        visit(items);
+        return;
+    }
+    for i in k..items.len() {
+        items[k..=i].rotate_right(1);
+        for_each_permutation(items, k + 1, visit);
+        items[k..=i].rotate_left(1);
+    }
+}
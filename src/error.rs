@@ -1,3 +1,4 @@
+use crate::reasoning::{SyntaxError, Violation};
 use thiserror::Error;
 
 /// Cognition errors
@@ -12,9 +13,18 @@ pub enum CognitionError {
     #[error("Belief revision failed: {0}")]
     BeliefRevisionFailed(String),
 
+    #[error("Decision failed: {0}")]
+    DecisionFailed(String),
+
     #[error("Reasoning error: {0}")]
     ReasoningError(String),
 
+    #[error("Syntax error: {0}")]
+    SyntaxError(#[from] SyntaxError),
+
+    #[error("Property violated: {0}")]
+    PropertyViolated(Violation),
+
     #[error("Cognition error: {0}")]
     Other(String),
 }
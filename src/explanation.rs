@@ -0,0 +1,177 @@
+//! Uniform explanations of reasoning, planning and decision results
+
+use std::fmt;
+
+/// Why a step of an explanation holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    /// Known from the start: a premise or a fact of the initial state
+    Fact,
+    /// Derived by the named rule from the supporting facts below it
+    Rule(String),
+    /// Comparison that held
+    Builtin,
+    /// Plan whose actions are listed below it
+    Plan,
+    /// Action achieving the listed goal facts
+    Action(Vec<String>),
+    /// Option chosen for the highest expected utility
+    Chosen(f64),
+    /// Option passed over, with its expected utility
+    Rejected(f64),
+}
+
+/// Explanation of a result as a tree of reasons
+///
+/// Produced by `ReasoningEngine::explain` for inferred facts,
+/// `Planner::explain` for plans and `Choice::explain` for decisions.
+/// `Display` renders it as indented text, `to_json` as a JSON object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    subject: String,
+    reason: Reason,
+    children: Vec<Explanation>,
+}
+
+impl Explanation {
+    /// Create a new explanation
+    pub fn new(subject: impl Into<String>, reason: Reason) -> Self {
+        Self {
+            subject: subject.into(),
+            reason,
+            children: Vec::new(),
+        }
+    }
+
+    /// Add a supporting explanation
+    pub fn with_child(mut self, child: Explanation) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Get what is explained
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Get the reason
+    pub fn reason(&self) -> &Reason {
+        &self.reason
+    }
+
+    /// Get the supporting explanations
+    pub fn children(&self) -> &[Explanation] {
+        &self.children
+    }
+
+    /// Find the explanation of a subject anywhere in the tree
+    pub fn find(&self, subject: &str) -> Option<&Explanation> {
+        if self.subject == subject {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find(subject))
+    }
+
+    /// Render as a JSON object
+    ///
+    /// Every node has `subject`, `reason` and `children`; rules add `rule`,
+    /// actions `achieves` and decision options `utility`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        json.push_str("{\"subject\":");
+        push_string(json, &self.subject);
+        let kind = match &self.reason {
+            Reason::Fact => "fact",
+            Reason::Rule(_) => "rule",
+            Reason::Builtin => "builtin",
+            Reason::Plan => "plan",
+            Reason::Action(_) => "action",
+            Reason::Chosen(_) => "chosen",
+            Reason::Rejected(_) => "rejected",
+        };
+        json.push_str(",\"reason\":\"");
+        json.push_str(kind);
+        json.push('"');
+        match &self.reason {
+            Reason::Rule(name) => {
+                json.push_str(",\"rule\":");
+                push_string(json, name);
+            }
+            Reason::Action(achieves) => {
+                json.push_str(",\"achieves\":[");
+                for (i, fact) in achieves.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    push_string(json, fact);
+                }
+                json.push(']');
+            }
+            Reason::Chosen(utility) | Reason::Rejected(utility) => {
+                json.push_str(",\"utility\":");
+                push_number(json, *utility);
+            }
+            Reason::Fact | Reason::Builtin | Reason::Plan => {}
+        }
+        json.push_str(",\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            child.write_json(json);
+        }
+        json.push_str("]}");
+    }
+
+    fn render(&self, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{} ", "  ".repeat(depth), self.subject)?;
+        match &self.reason {
+            Reason::Fact => writeln!(f, "[fact]")?,
+            Reason::Rule(name) => writeln!(f, "[rule {}]", name)?,
+            Reason::Builtin => writeln!(f, "[builtin]")?,
+            Reason::Plan => writeln!(f, "[plan]")?,
+            Reason::Action(achieves) if achieves.is_empty() => writeln!(f, "[action]")?,
+            Reason::Action(achieves) => writeln!(f, "[achieves {}]", achieves.join(", "))?,
+            Reason::Chosen(utility) => writeln!(f, "[chosen, utility {}]", utility)?,
+            Reason::Rejected(utility) => writeln!(f, "[rejected, utility {}]", utility)?,
+        }
+        self.children
+            .iter()
+            .try_for_each(|c| c.render(depth + 1, f))
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(0, f)
+    }
+}
+
+fn push_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn push_number(json: &mut String, value: f64) {
+    if value.is_finite() {
+        json.push_str(&value.to_string());
+    } else {
+        json.push_str("null");
+    }
+}
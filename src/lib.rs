@@ -6,11 +6,13 @@
 pub mod bdi;
 pub mod decision;
 pub mod error;
+pub mod explanation;
 pub mod planning;
 pub mod prelude;
 pub mod reasoning;
 
 // Re-exports
-pub use bdi::{Belief, BeliefBase, Desire, Goal, Intention, IntentionStack};
+pub use bdi::{Belief, BeliefBase, Desire, Goal, Intention, IntentionStack, Value};
 pub use error::CognitionError;
+pub use explanation::{Explanation, Reason};
 pub use planning::{Action, Plan, Planner};
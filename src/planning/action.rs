@@ -1,3 +1,4 @@
+use super::State;
 use serde::{Deserialize, Serialize};
 
 /// An action that can be performed
@@ -5,6 +6,10 @@ use serde::{Deserialize, Serialize};
 pub struct Action {
     name: String,
     parameters: Vec<String>,
+    #[serde(default)]
+    preconditions: Vec<(String, String)>,
+    #[serde(default)]
+    effects: Vec<(String, String)>,
 }
 
 impl Action {
@@ -13,6 +18,8 @@ impl Action {
         Self {
             name: name.into(),
             parameters: Vec::new(),
+            preconditions: Vec::new(),
+            effects: Vec::new(),
         }
     }
 
@@ -22,6 +29,18 @@ impl Action {
         self
     }
 
+    /// Require a state variable to have a value before the action
+    pub fn with_precondition(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.preconditions.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set a state variable to a value when the action is performed
+    pub fn with_effect(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.effects.push((key.into(), value.into()));
+        self
+    }
+
     /// Get the name
     pub fn name(&self) -> &str {
         &self.name
@@ -31,4 +50,26 @@ impl Action {
     pub fn parameters(&self) -> &[String] {
         &self.parameters
     }
+
+    /// Get preconditions
+    pub fn preconditions(&self) -> &[(String, String)] {
+        &self.preconditions
+    }
+
+    /// Get effects
+    pub fn effects(&self) -> &[(String, String)] {
+        &self.effects
+    }
+
+    /// Check if every precondition holds in a state
+    pub fn is_applicable(&self, state: &State) -> bool {
+        self.preconditions.iter().all(|(k, v)| state.matches(k, v))
+    }
+
+    /// State after performing the action
+    pub fn apply(&self, state: &State) -> State {
+        self.effects.iter().fold(state.clone(), |state, (k, v)| {
+            state.set(k.clone(), v.clone())
+        })
+    }
 }
@@ -1,4 +1,5 @@
 use super::{Action, Plan, State};
+use crate::explanation::{Explanation, Reason};
 use crate::CognitionError;
 
 /// Simple planner
@@ -36,6 +37,78 @@ impl Planner {
     pub fn actions(&self) -> &[Action] {
         &self.actions
     }
+
+    /// Explain how a plan reaches a goal from an initial state
+    ///
+    /// Each action is credited with the goal facts it leaves in place at the
+    /// end of the plan; goal facts true from the start and never changed
+    /// are listed first as facts. Fails if an action's preconditions do not
+    /// hold when it is reached or the goal is not met afterwards.
+    pub fn explain(
+        &self,
+        initial: &State,
+        goal: &State,
+        plan: &Plan,
+    ) -> Result<Explanation, CognitionError> {
+        let mut goals: Vec<(&String, &String)> = goal.variables().iter().collect();
+        goals.sort();
+        let mut state = initial.clone();
+        // Step that last set each goal fact, if any
+        let mut achieved_by: Vec<Option<usize>> = vec![None; goals.len()];
+
+        for (step, action) in plan.actions().iter().enumerate() {
+            if let Some((key, value)) = action
+                .preconditions()
+                .iter()
+                .find(|(k, v)| !state.matches(k, v))
+            {
+                return Err(CognitionError::PlanningFailed(format!(
+                    "precondition {}={} of step {} '{}' does not hold",
+                    key,
+                    value,
+                    step + 1,
+                    action.name()
+                )));
+            }
+            state = action.apply(&state);
+            for (i, (key, _)) in goals.iter().enumerate() {
+                if action.effects().iter().any(|(k, _)| k == *key) {
+                    achieved_by[i] = Some(step);
+                }
+            }
+        }
+
+        let unmet: Vec<String> = goals
+            .iter()
+            .filter(|(k, v)| !state.matches(k, v))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        if !unmet.is_empty() {
+            return Err(CognitionError::GoalNotAchievable(unmet.join(", ")));
+        }
+
+        let mut explanation = Explanation::new(plan.name(), Reason::Plan);
+        for ((key, value), _) in goals.iter().zip(&achieved_by).filter(|(_, s)| s.is_none()) {
+            explanation = explanation
+                .with_child(Explanation::new(format!("{}={}", key, value), Reason::Fact));
+        }
+        for (step, action) in plan.actions().iter().enumerate() {
+            let achieves = goals
+                .iter()
+                .zip(&achieved_by)
+                .filter(|(_, s)| **s == Some(step))
+                .map(|((k, v), _)| format!("{}={}", k, v))
+                .collect();
+            let subject = if action.parameters().is_empty() {
+                action.name().to_string()
+            } else {
+                format!("{}({})", action.name(), action.parameters().join(", "))
+            };
+            explanation =
+                explanation.with_child(Explanation::new(subject, Reason::Action(achieves)));
+        }
+        Ok(explanation)
+    }
 }
 
 impl Default for Planner {
@@ -9,10 +9,10 @@ pub use crate::bdi::{
 pub use crate::planning::{Action, Plan, Planner, State};
 
 // Reasoning re-exports
-pub use crate::reasoning::{ReasoningEngine, Rule};
+pub use crate::reasoning::{ReasoningEngine, Rule, Term};
 
 // Decision re-exports
-pub use crate::decision::UtilityFunction;
+pub use crate::decision::{Ballot, Election, TieBreak, UtilityFunction, VotingRule};
 
 // Error re-export
 pub use crate::CognitionError;
@@ -0,0 +1,281 @@
+use super::builtin;
+use super::query::rename;
+use super::{unify, ReasoningEngine, Substitution, Term};
+use crate::CognitionError;
+use std::fmt;
+
+/// Set of assumptions that, with the known facts and rules, entails the
+/// observations
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbductiveExplanation {
+    assumptions: Vec<Term>,
+    cost: f64,
+}
+
+impl AbductiveExplanation {
+    /// Get the assumed facts, sorted
+    pub fn assumptions(&self) -> &[Term] {
+        &self.assumptions
+    }
+
+    /// Get the total cost of the assumptions
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Check if a fact is assumed
+    pub fn assumes(&self, fact: &str) -> bool {
+        self.assumptions.contains(&Term::parse_lenient(fact))
+    }
+}
+
+impl fmt::Display for AbductiveExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.assumptions.is_empty() {
+            write!(f, "(no assumptions)")?;
+        }
+        for (i, assumption) in self.assumptions.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", assumption)?;
+        }
+        write!(f, " [cost {}]", self.cost)
+    }
+}
+
+/// Search state: goals left to prove and what has been assumed
+#[derive(Clone)]
+struct Branch {
+    goals: Vec<(Term, usize)>,
+    bindings: Substitution,
+    assumptions: Vec<Term>,
+}
+
+/// Abductive explanation of observations through an engine's strict rules
+///
+/// Goals are resolved backwards against facts and rule conclusions as in
+/// `ReasoningEngine::query`; a goal matching a declared abducible may also
+/// be assumed. Explanations must be ground, violate no integrity
+/// constraint once the engine infers from them, and be minimal: no other
+/// explanation assumes a subset. They are ranked by cost, then size.
+#[derive(Debug, Clone)]
+pub struct Abduction {
+    abducibles: Vec<(Term, f64)>,
+    constraints: Vec<Vec<Term>>,
+    max_depth: usize,
+    max_assumptions: Option<usize>,
+}
+
+impl Default for Abduction {
+    fn default() -> Self {
+        Self {
+            abducibles: Vec::new(),
+            constraints: Vec::new(),
+            max_depth: 16,
+            max_assumptions: None,
+        }
+    }
+}
+
+impl Abduction {
+    /// Create an abduction problem with no abducibles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow facts matching a pattern such as `broken(X)` to be assumed
+    pub fn with_abducible(mut self, pattern: &str, cost: f64) -> Self {
+        self.abducibles
+            .push((Term::parse_lenient(pattern), cost.max(0.0)));
+        self
+    }
+
+    /// Add an integrity constraint: the conditions must never all hold
+    pub fn with_constraint(mut self, conditions: &[&str]) -> Self {
+        self.constraints
+            .push(conditions.iter().map(|c| Term::parse_lenient(c)).collect());
+        self
+    }
+
+    /// Limit how many rules deep the search goes (default 16)
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Limit how many facts one explanation may assume
+    pub fn with_max_assumptions(mut self, count: usize) -> Self {
+        self.max_assumptions = Some(count);
+        self
+    }
+
+    /// Explanations of one observation, best first
+    pub fn explain(
+        &self,
+        engine: &ReasoningEngine,
+        observation: &str,
+    ) -> Result<Vec<AbductiveExplanation>, CognitionError> {
+        self.explain_all(engine, &[observation])
+    }
+
+    /// Explanations of several observations together, best first
+    pub fn explain_all(
+        &self,
+        engine: &ReasoningEngine,
+        observations: &[&str],
+    ) -> Result<Vec<AbductiveExplanation>, CognitionError> {
+        let goals = observations
+            .iter()
+            .rev()
+            .map(|o| Term::parse(o).map(|t| (t, 0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut candidates: Vec<Vec<Term>> = Vec::new();
+        let mut stack = vec![Branch {
+            goals,
+            bindings: Substitution::new(),
+            assumptions: Vec::new(),
+        }];
+        let mut renames = 0;
+
+        while let Some(mut branch) = stack.pop() {
+            let Some((goal, depth)) = branch.goals.pop() else {
+                let mut assumed: Vec<Term> = branch
+                    .assumptions
+                    .iter()
+                    .map(|a| branch.bindings.apply(a))
+                    .collect();
+                if assumed.iter().all(Term::is_ground) {
+                    assumed.sort_by_key(Term::to_string);
+                    assumed.dedup();
+                    if !candidates.contains(&assumed) {
+                        candidates.push(assumed);
+                    }
+                }
+                continue;
+            };
+            let term = branch.bindings.apply(&goal);
+            if builtin::is_builtin(&term) {
+                if builtin::holds(&term, &branch.bindings) {
+                    stack.push(branch);
+                }
+                continue;
+            }
+            let mut alternatives = Vec::new();
+
+            for fact in engine.candidates(&term) {
+                if let Some(bindings) = unify(&term, fact, &branch.bindings) {
+                    let mut next = branch.clone();
+                    next.bindings = bindings;
+                    alternatives.push(next);
+                }
+            }
+
+            if depth < self.max_depth {
+                for rule in engine.compiled_rules() {
+                    for conclusion in &rule.conclusions {
+                        renames += 1;
+                        let Some(bindings) =
+                            unify(&term, &rename(conclusion, renames), &branch.bindings)
+                        else {
+                            continue;
+                        };
+                        let mut next = branch.clone();
+                        next.bindings = bindings;
+                        next.goals.extend(
+                            rule.conditions
+                                .iter()
+                                .rev()
+                                .map(|c| (rename(c, renames), depth + 1)),
+                        );
+                        alternatives.push(next);
+                    }
+                }
+            }
+
+            let room = self
+                .max_assumptions
+                .is_none_or(|max| branch.assumptions.len() < max);
+            let abducible = self
+                .abducibles
+                .iter()
+                .any(|(pattern, _)| unify(&term, pattern, &Substitution::new()).is_some());
+            if room && abducible {
+                let mut next = branch;
+                next.assumptions.push(term);
+                alternatives.push(next);
+            }
+
+            stack.extend(alternatives.into_iter().rev());
+        }
+
+        candidates.retain(|assumed| self.consistent(engine, assumed));
+        let minimal: Vec<&Vec<Term>> = candidates
+            .iter()
+            .filter(|a| {
+                !candidates
+                    .iter()
+                    .any(|b| b.len() < a.len() && b.iter().all(|t| a.contains(t)))
+            })
+            .collect();
+        let mut explanations: Vec<AbductiveExplanation> = minimal
+            .into_iter()
+            .map(|assumptions| AbductiveExplanation {
+                cost: assumptions.iter().map(|a| self.cost(a)).sum(),
+                assumptions: assumptions.clone(),
+            })
+            .collect();
+        explanations.sort_by(|a, b| {
+            a.cost
+                .total_cmp(&b.cost)
+                .then(a.assumptions.len().cmp(&b.assumptions.len()))
+        });
+        Ok(explanations)
+    }
+
+    fn cost(&self, assumption: &Term) -> f64 {
+        self.abducibles
+            .iter()
+            .find(|(pattern, _)| unify(assumption, pattern, &Substitution::new()).is_some())
+            .map_or(0.0, |(_, cost)| *cost)
+    }
+
+    /// Check that no integrity constraint holds after assuming the facts
+    fn consistent(&self, engine: &ReasoningEngine, assumed: &[Term]) -> bool {
+        if self.constraints.is_empty() {
+            return true;
+        }
+        let mut extended = engine.clone();
+        for assumption in assumed {
+            extended.add_term(assumption.clone());
+        }
+        extended.infer();
+        !self
+            .constraints
+            .iter()
+            .any(|conditions| satisfiable(&extended, conditions))
+    }
+}
+
+/// Check if some facts of the engine satisfy every condition together
+fn satisfiable(engine: &ReasoningEngine, conditions: &[Term]) -> bool {
+    let mut partial = vec![Substitution::new()];
+    for condition in conditions.iter().filter(|c| !builtin::is_builtin(c)) {
+        partial = partial
+            .iter()
+            .flat_map(|bindings| {
+                let pattern = bindings.apply(condition);
+                engine
+                    .candidates(&pattern)
+                    .filter_map(|fact| unify(&pattern, fact, bindings))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    partial.iter().any(|bindings| {
+        conditions
+            .iter()
+            .filter(|c| builtin::is_builtin(c))
+            .all(|c| builtin::holds(c, bindings))
+    })
+}
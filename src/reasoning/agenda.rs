@@ -0,0 +1,68 @@
+use super::engine::CompiledRule;
+use super::matcher::{Activation, FactId};
+use std::cmp::Ordering;
+
+/// Order in which eligible rule instantiations fire
+///
+/// Salience is always compared first; the strategy breaks ties between
+/// activations of equal salience. Every strategy falls back to activation
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Oldest activation first
+    #[default]
+    Fifo,
+    /// Activation matching the most recent facts first (OPS5 LEX)
+    Lex,
+    /// Most recent fact for the first condition first, then LEX (OPS5 MEA)
+    Mea,
+    /// Rules with more conditions first, then LEX
+    Specificity,
+}
+
+/// Index of the activation that should fire next
+pub(crate) fn select(
+    activations: &[Activation],
+    rules: &[CompiledRule],
+    strategy: ConflictStrategy,
+) -> Option<usize> {
+    (0..activations.len())
+        .min_by(|&a, &b| compare(&activations[a], &activations[b], rules, strategy))
+}
+
+/// `Less` means `a` fires before `b`
+fn compare(
+    a: &Activation,
+    b: &Activation,
+    rules: &[CompiledRule],
+    strategy: ConflictStrategy,
+) -> Ordering {
+    let salience = rules[b.rule].salience.cmp(&rules[a.rule].salience);
+    let preference = match strategy {
+        ConflictStrategy::Fifo => Ordering::Equal,
+        ConflictStrategy::Lex => lex(a, b),
+        ConflictStrategy::Mea => b
+            .facts
+            .first()
+            .cmp(&a.facts.first())
+            .then_with(|| lex(a, b)),
+        ConflictStrategy::Specificity => rules[b.rule]
+            .conditions
+            .len()
+            .cmp(&rules[a.rule].conditions.len())
+            .then_with(|| lex(a, b)),
+    };
+    salience
+        .then(preference)
+        .then_with(|| a.sequence.cmp(&b.sequence))
+}
+
+/// Compare fact timestamps newest first; a longer list wins a common prefix
+fn lex(a: &Activation, b: &Activation) -> Ordering {
+    let newest = |facts: &[FactId]| {
+        let mut sorted = facts.to_vec();
+        sorted.sort_unstable_by(|x, y| y.cmp(x));
+        sorted
+    };
+    newest(&b.facts).cmp(&newest(&a.facts))
+}
@@ -0,0 +1,545 @@
+use super::builtin;
+use super::{unify, ReasoningEngine, Rule, Substitution, Term};
+use crate::CognitionError;
+use std::collections::{HashMap, HashSet};
+
+/// Acceptability semantics for an argumentation framework
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Semantics {
+    /// Least complete extension; the most sceptical semantics
+    #[default]
+    Grounded,
+    /// Maximal admissible extensions
+    Preferred,
+    /// Conflict-free extensions attacking every outside argument
+    Stable,
+}
+
+/// Dung-style abstract argumentation framework
+///
+/// Extensions are computed by search for preferred and stable semantics,
+/// which is exponential in the number of arguments the grounded extension
+/// leaves undecided.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArgumentationFramework {
+    arguments: Vec<String>,
+    /// Attackers of each argument
+    attackers: Vec<Vec<usize>>,
+}
+
+impl ArgumentationFramework {
+    /// Create an empty framework
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an argument; adding a known name has no effect
+    pub fn add_argument(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        if self.index(&name).is_none() {
+            self.arguments.push(name);
+            self.attackers.push(Vec::new());
+        }
+    }
+
+    /// Add an attack between two known arguments
+    pub fn add_attack(&mut self, attacker: &str, target: &str) -> Result<(), CognitionError> {
+        let a = self.argument(attacker)?;
+        let t = self.argument(target)?;
+        if !self.attackers[t].contains(&a) {
+            self.attackers[t].push(a);
+        }
+        Ok(())
+    }
+
+    /// Get all arguments
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
+    /// Get the arguments attacking an argument
+    pub fn attackers_of(&self, argument: &str) -> Vec<&str> {
+        self.index(argument)
+            .map(|i| {
+                self.attackers[i]
+                    .iter()
+                    .map(|&a| self.arguments[a].as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every extension under the given semantics
+    pub fn extensions(&self, semantics: Semantics) -> Vec<Vec<&str>> {
+        let extensions = match semantics {
+            Semantics::Grounded => vec![self.grounded_set()],
+            Semantics::Preferred => self.preferred_sets(),
+            Semantics::Stable => self
+                .preferred_sets()
+                .into_iter()
+                .filter(|set| self.is_stable(set))
+                .collect(),
+        };
+        extensions.iter().map(|set| self.names(set)).collect()
+    }
+
+    /// The grounded extension
+    pub fn grounded(&self) -> Vec<&str> {
+        self.names(&self.grounded_set())
+    }
+
+    /// Arguments in every extension (sceptical acceptance)
+    pub fn accepted(&self, semantics: Semantics) -> Vec<&str> {
+        self.names(&self.sceptical(semantics))
+    }
+
+    /// Arguments in at least one extension (credulous acceptance)
+    pub fn credulously_accepted(&self, semantics: Semantics) -> Vec<&str> {
+        let sets = match semantics {
+            Semantics::Grounded => vec![self.grounded_set()],
+            Semantics::Preferred => self.preferred_sets(),
+            Semantics::Stable => self
+                .preferred_sets()
+                .into_iter()
+                .filter(|set| self.is_stable(set))
+                .collect(),
+        };
+        let union: Vec<bool> = (0..self.arguments.len())
+            .map(|a| sets.iter().any(|set| set[a]))
+            .collect();
+        self.names(&union)
+    }
+
+    fn sceptical(&self, semantics: Semantics) -> Vec<bool> {
+        let sets = match semantics {
+            Semantics::Grounded => return self.grounded_set(),
+            Semantics::Preferred => self.preferred_sets(),
+            Semantics::Stable => self
+                .preferred_sets()
+                .into_iter()
+                .filter(|set| self.is_stable(set))
+                .collect(),
+        };
+        (0..self.arguments.len())
+            .map(|a| !sets.is_empty() && sets.iter().all(|set| set[a]))
+            .collect()
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.arguments.iter().position(|a| a == name)
+    }
+
+    fn argument(&self, name: &str) -> Result<usize, CognitionError> {
+        self.index(name)
+            .ok_or_else(|| CognitionError::ReasoningError(format!("unknown argument '{}'", name)))
+    }
+
+    fn names(&self, set: &[bool]) -> Vec<&str> {
+        (0..self.arguments.len())
+            .filter(|&a| set[a])
+            .map(|a| self.arguments[a].as_str())
+            .collect()
+    }
+
+    /// Check if `set` attacks argument `a`
+    fn attacks(&self, set: &[bool], a: usize) -> bool {
+        self.attackers[a].iter().any(|&b| set[b])
+    }
+
+    /// Least fixpoint of "accept what the current set defends"
+    fn grounded_set(&self) -> Vec<bool> {
+        let mut set = vec![false; self.arguments.len()];
+        loop {
+            let next: Vec<bool> = (0..self.arguments.len())
+                .map(|a| self.attackers[a].iter().all(|&b| self.attacks(&set, b)))
+                .collect();
+            if next == set {
+                return set;
+            }
+            set = next;
+        }
+    }
+
+    fn is_admissible(&self, set: &[bool]) -> bool {
+        (0..self.arguments.len())
+            .filter(|&a| set[a])
+            .all(|a| self.attackers[a].iter().all(|&b| self.attacks(set, b)))
+    }
+
+    fn is_stable(&self, set: &[bool]) -> bool {
+        (0..self.arguments.len()).all(|a| set[a] || self.attacks(set, a))
+    }
+
+    /// Maximal admissible sets, by search over conflict-free sets
+    ///
+    /// Every preferred extension contains the grounded one, so only the
+    /// arguments it leaves undecided are branched on.
+    fn preferred_sets(&self) -> Vec<Vec<bool>> {
+        let grounded = self.grounded_set();
+        let open: Vec<usize> = (0..self.arguments.len())
+            .filter(|&a| {
+                !grounded[a] && !self.attacks(&grounded, a) && !self.attackers[a].contains(&a)
+            })
+            .collect();
+        let mut admissible = Vec::new();
+        let mut set = grounded;
+        self.search(&open, 0, &mut set, &mut admissible);
+        let maximal: Vec<Vec<bool>> = admissible
+            .iter()
+            .filter(|a| {
+                !admissible
+                    .iter()
+                    .any(|b| b != *a && a.iter().zip(b).all(|(x, y)| !x || *y))
+            })
+            .cloned()
+            .collect();
+        maximal
+    }
+
+    fn search(&self, open: &[usize], next: usize, set: &mut Vec<bool>, found: &mut Vec<Vec<bool>>) {
+        if !self.can_defend(set, &open[next..]) {
+            return;
+        }
+        if next == open.len() {
+            if self.is_admissible(set) {
+                found.push(set.clone());
+            }
+            return;
+        }
+        // Include the argument only if the set stays conflict-free
+        let a = open[next];
+        let conflict = self.attacks(set, a)
+            || open[..next]
+                .iter()
+                .any(|&b| set[b] && self.attackers[b].contains(&a));
+        if !conflict {
+            set[a] = true;
+            self.search(open, next + 1, set, found);
+            set[a] = false;
+        }
+        self.search(open, next + 1, set, found);
+    }
+
+    /// Check if every attacker of a member is, or may still be, counter-attacked
+    fn can_defend(&self, set: &[bool], remaining: &[usize]) -> bool {
+        (0..self.arguments.len()).filter(|&a| set[a]).all(|a| {
+            self.attackers[a].iter().all(|&b| {
+                self.attackers[b]
+                    .iter()
+                    .any(|&c| set[c] || remaining.contains(&c))
+            })
+        })
+    }
+}
+
+/// How one structured argument attacks another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackKind {
+    /// Concludes the negation `neg(c)` of a conclusion `c` used by the target
+    Rebut,
+    /// Concludes `undercut(rule)` for a rule the target applies
+    Undercut,
+}
+
+/// Argument for a conclusion: a fact, or a rule applied to sub-arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    conclusion: Term,
+    rule: Option<String>,
+    subarguments: Vec<usize>,
+}
+
+impl Argument {
+    /// Get the conclusion
+    pub fn conclusion(&self) -> &Term {
+        &self.conclusion
+    }
+
+    /// Get the rule applied last (`None` for facts)
+    pub fn rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    /// Get the indices of the sub-arguments
+    pub fn subarguments(&self) -> &[usize] {
+        &self.subarguments
+    }
+}
+
+/// Why an argument was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defeat {
+    conclusion: Term,
+    attacker: Term,
+    kind: AttackKind,
+}
+
+impl Defeat {
+    /// Get the conclusion of the defeated argument
+    pub fn conclusion(&self) -> &Term {
+        &self.conclusion
+    }
+
+    /// Get the conclusion of the accepted argument that attacked it
+    pub fn attacker(&self) -> &Term {
+        &self.attacker
+    }
+
+    /// Get the kind of attack
+    pub fn kind(&self) -> AttackKind {
+        self.kind
+    }
+}
+
+/// Outcome of resolving conflicting arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    accepted: Vec<Term>,
+    defeated: Vec<Defeat>,
+    undecided: Vec<Term>,
+}
+
+impl Resolution {
+    /// Conclusions of accepted arguments
+    pub fn accepted(&self) -> &[Term] {
+        &self.accepted
+    }
+
+    /// Each accepted attack on a rejected argument
+    pub fn defeated(&self) -> &[Defeat] {
+        &self.defeated
+    }
+
+    /// Conclusions neither accepted nor defeated
+    pub fn undecided(&self) -> &[Term] {
+        &self.undecided
+    }
+
+    /// Check if a conclusion was accepted
+    pub fn is_accepted(&self, conclusion: &str) -> bool {
+        let conclusion = Term::parse_lenient(conclusion);
+        self.accepted.contains(&conclusion)
+    }
+}
+
+/// Structured arguments built from rule derivations
+///
+/// Facts are arguments on their own; applying a rule to arguments for its
+/// conditions gives an argument for its conclusion. A conclusion `neg(c)`
+/// rebuts every argument using `c`, and `undercut(name)` undercuts every
+/// argument applying the rule `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentSet {
+    arguments: Vec<Argument>,
+    framework: ArgumentationFramework,
+    kinds: HashMap<(usize, usize), AttackKind>,
+}
+
+impl ArgumentSet {
+    /// Build every non-circular argument from rules and facts
+    pub fn build(rules: &[Rule], facts: &[&str]) -> Result<Self, CognitionError> {
+        let facts = facts
+            .iter()
+            .map(|f| Term::parse(f))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_terms(rules, facts))
+    }
+
+    /// Build arguments from an engine's rules and the facts asserted into it
+    pub fn from_engine(engine: &ReasoningEngine) -> Self {
+        Self::from_terms(engine.rules(), engine.premises().cloned().collect())
+    }
+
+    fn from_terms(rules: &[Rule], facts: Vec<Term>) -> Self {
+        let mut arguments: Vec<Argument> = Vec::new();
+        for fact in facts {
+            if !arguments.iter().any(|a| a.conclusion == fact) {
+                arguments.push(Argument {
+                    conclusion: fact,
+                    rule: None,
+                    subarguments: Vec::new(),
+                });
+            }
+        }
+
+        let compiled: Vec<(&Rule, Vec<Term>, Vec<Term>)> = rules
+            .iter()
+            .map(|r| (r, r.condition_terms(), r.conclusion_terms()))
+            .collect();
+        let mut seen: HashSet<(String, Vec<usize>, Term)> = HashSet::new();
+        loop {
+            let mut added = false;
+            for (rule, conditions, conclusions) in &compiled {
+                for (subarguments, bindings) in Self::combinations(&arguments, conditions) {
+                    for conclusion in conclusions {
+                        let conclusion = bindings.apply(conclusion);
+                        let key = (
+                            rule.name().to_string(),
+                            subarguments.clone(),
+                            conclusion.clone(),
+                        );
+                        let circular = subarguments
+                            .iter()
+                            .any(|&s| Self::concludes(&arguments, s, &conclusion));
+                        if !conclusion.is_ground() || circular || !seen.insert(key) {
+                            continue;
+                        }
+                        arguments.push(Argument {
+                            conclusion,
+                            rule: Some(rule.name().to_string()),
+                            subarguments: subarguments.clone(),
+                        });
+                        added = true;
+                    }
+                }
+            }
+            if !added {
+                break;
+            }
+        }
+
+        let mut set = Self {
+            framework: ArgumentationFramework::new(),
+            kinds: HashMap::new(),
+            arguments,
+        };
+        set.add_attacks();
+        set
+    }
+
+    /// Sub-argument choices satisfying every condition, with their bindings
+    fn combinations(
+        arguments: &[Argument],
+        conditions: &[Term],
+    ) -> Vec<(Vec<usize>, Substitution)> {
+        let mut partial = vec![(Vec::new(), Substitution::new())];
+        for condition in conditions.iter().filter(|c| !builtin::is_builtin(c)) {
+            let mut next = Vec::new();
+            for (chosen, bindings) in &partial {
+                let pattern = bindings.apply(condition);
+                for (i, argument) in arguments.iter().enumerate() {
+                    if let Some(extended) = unify(&pattern, &argument.conclusion, bindings) {
+                        let mut chosen = chosen.clone();
+                        chosen.push(i);
+                        next.push((chosen, extended));
+                    }
+                }
+            }
+            partial = next;
+        }
+        partial.retain(|(_, bindings)| {
+            conditions
+                .iter()
+                .filter(|c| builtin::is_builtin(c))
+                .all(|c| builtin::holds(c, bindings))
+        });
+        partial
+    }
+
+    /// Check if an argument or any of its sub-arguments concludes `term`
+    fn concludes(arguments: &[Argument], index: usize, term: &Term) -> bool {
+        let argument = &arguments[index];
+        argument.conclusion == *term
+            || argument
+                .subarguments
+                .iter()
+                .any(|&s| Self::concludes(arguments, s, term))
+    }
+
+    /// Check if an argument or any of its sub-arguments applies `rule`
+    fn applies(arguments: &[Argument], index: usize, rule: &str) -> bool {
+        let argument = &arguments[index];
+        argument.rule.as_deref() == Some(rule)
+            || argument
+                .subarguments
+                .iter()
+                .any(|&s| Self::applies(arguments, s, rule))
+    }
+
+    fn add_attacks(&mut self) {
+        for i in 0..self.arguments.len() {
+            self.framework.add_argument(Self::name(i));
+        }
+        for a in 0..self.arguments.len() {
+            for b in 0..self.arguments.len() {
+                let conclusion = &self.arguments[a].conclusion;
+                let kind = match conclusion {
+                    Term::Compound(f, args) if f == "neg" && args.len() == 1 => {
+                        Self::concludes(&self.arguments, b, &args[0]).then_some(AttackKind::Rebut)
+                    }
+                    Term::Compound(f, args) if f == "undercut" && args.len() == 1 => {
+                        let rule = match &args[0] {
+                            Term::Atom(name) => name.clone(),
+                            other => other.to_string(),
+                        };
+                        Self::applies(&self.arguments, b, &rule).then_some(AttackKind::Undercut)
+                    }
+                    _ => {
+                        let negated = Term::compound("neg", vec![conclusion.clone()]);
+                        Self::concludes(&self.arguments, b, &negated).then_some(AttackKind::Rebut)
+                    }
+                };
+                if let Some(kind) = kind {
+                    self.kinds.insert((a, b), kind);
+                    self.framework
+                        .add_attack(&Self::name(a), &Self::name(b))
+                        .expect("both arguments were added");
+                }
+            }
+        }
+    }
+
+    fn name(index: usize) -> String {
+        format!("A{}", index)
+    }
+
+    /// Get all arguments; argument `i` is named `A{i}` in the framework
+    pub fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    /// Get the underlying abstract framework
+    pub fn framework(&self) -> &ArgumentationFramework {
+        &self.framework
+    }
+
+    /// Accepted conclusions under the given semantics, and why others lost
+    ///
+    /// With several extensions only arguments in all of them are accepted.
+    pub fn resolve(&self, semantics: Semantics) -> Resolution {
+        let accepted_set = self.framework.sceptical(semantics);
+        let mut accepted = Vec::new();
+        let mut defeated = Vec::new();
+        let mut undecided = Vec::new();
+        for (i, argument) in self.arguments.iter().enumerate() {
+            if accepted_set[i] {
+                if !accepted.contains(&argument.conclusion) {
+                    accepted.push(argument.conclusion.clone());
+                }
+                continue;
+            }
+            let defeaters: Vec<usize> = self.framework.attackers[i]
+                .iter()
+                .copied()
+                .filter(|&a| accepted_set[a])
+                .collect();
+            if defeaters.is_empty() && !undecided.contains(&argument.conclusion) {
+                undecided.push(argument.conclusion.clone());
+            }
+            for a in defeaters {
+                defeated.push(Defeat {
+                    conclusion: argument.conclusion.clone(),
+                    attacker: self.arguments[a].conclusion.clone(),
+                    kind: self.kinds[&(a, i)],
+                });
+            }
+        }
+        // A conclusion with an accepted argument is not in doubt
+        undecided.retain(|c| !accepted.contains(c));
+        Resolution {
+            accepted,
+            defeated,
+            undecided,
+        }
+    }
+}
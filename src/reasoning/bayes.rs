@@ -0,0 +1,557 @@
+use crate::bdi::{Belief, BeliefBase};
+use crate::CognitionError;
+use std::collections::HashMap;
+
+/// Observed states, keyed by variable name
+pub type Evidence = HashMap<String, String>;
+
+/// Discrete random variable with its conditional probability table
+///
+/// The table has one row per combination of parent states, ordered with the
+/// last parent varying fastest; each row is a distribution over the
+/// variable's states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BayesNode {
+    name: String,
+    states: Vec<String>,
+    parents: Vec<usize>,
+    table: Vec<Vec<f64>>,
+}
+
+impl BayesNode {
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the states
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    /// Get the conditional probability table
+    pub fn table(&self) -> &[Vec<f64>] {
+        &self.table
+    }
+}
+
+/// Probability distribution over the states of one variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    variable: String,
+    states: Vec<String>,
+    probabilities: Vec<f64>,
+}
+
+impl Distribution {
+    /// Get the variable name
+    pub fn variable(&self) -> &str {
+        &self.variable
+    }
+
+    /// Get the probability of a state
+    pub fn probability(&self, state: &str) -> Option<f64> {
+        self.states
+            .iter()
+            .position(|s| s == state)
+            .map(|i| self.probabilities[i])
+    }
+
+    /// States with their probabilities
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.states
+            .iter()
+            .map(String::as_str)
+            .zip(self.probabilities.iter().copied())
+    }
+
+    /// Most probable state and its probability
+    pub fn most_likely(&self) -> (&str, f64) {
+        self.iter().fold(("", f64::NEG_INFINITY), |best, (s, p)| {
+            if p > best.1 {
+                (s, p)
+            } else {
+                best
+            }
+        })
+    }
+
+    /// The most probable state as a belief whose certainty is its probability
+    pub fn to_belief(&self) -> Belief {
+        let (state, probability) = self.most_likely();
+        Belief::with_certainty(self.variable.clone(), state, probability)
+    }
+}
+
+/// Discrete Bayesian network
+///
+/// Nodes are added parents first, so insertion order is a topological order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BayesianNetwork {
+    nodes: Vec<BayesNode>,
+}
+
+impl BayesianNetwork {
+    /// Create an empty network
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a variable with its parents and conditional probability table
+    pub fn add_node(
+        &mut self,
+        name: &str,
+        states: &[&str],
+        parents: &[&str],
+        table: Vec<Vec<f64>>,
+    ) -> Result<(), CognitionError> {
+        if self.index(name).is_some() {
+            return Err(Self::error(format!("variable '{}' already exists", name)));
+        }
+        if states.is_empty() {
+            return Err(Self::error(format!("variable '{}' has no states", name)));
+        }
+        let parents = parents
+            .iter()
+            .map(|p| {
+                self.index(p).ok_or_else(|| {
+                    Self::error(format!("parent '{}' of '{}' must be added first", p, name))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rows: usize = parents
+            .iter()
+            .map(|&p| self.nodes[p].states.len())
+            .product();
+        if table.len() != rows {
+            return Err(Self::error(format!(
+                "table of '{}' needs {} rows, got {}",
+                name,
+                rows,
+                table.len()
+            )));
+        }
+        for row in &table {
+            let sum: f64 = row.iter().sum();
+            if row.len() != states.len() || row.iter().any(|&p| p < 0.0) || (sum - 1.0).abs() > 1e-6
+            {
+                return Err(Self::error(format!(
+                    "every row of '{}' must be a distribution over {} states",
+                    name,
+                    states.len()
+                )));
+            }
+        }
+        self.nodes.push(BayesNode {
+            name: name.to_string(),
+            states: states.iter().map(|s| s.to_string()).collect(),
+            parents,
+            table,
+        });
+        Ok(())
+    }
+
+    /// Get a node by name
+    pub fn node(&self, name: &str) -> Option<&BayesNode> {
+        self.index(name).map(|i| &self.nodes[i])
+    }
+
+    /// Get all nodes in topological order
+    pub fn nodes(&self) -> &[BayesNode] {
+        &self.nodes
+    }
+
+    /// Evidence from beliefs whose key is a variable and value one of its states
+    pub fn evidence_from(&self, beliefs: &BeliefBase) -> Evidence {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let belief = beliefs.get(&node.name)?;
+                node.states
+                    .iter()
+                    .any(|s| belief.value() == s.as_str())
+                    .then(|| (node.name.clone(), belief.value().to_string()))
+            })
+            .collect()
+    }
+
+    /// Exact posterior of a variable by variable elimination
+    pub fn query(
+        &self,
+        variable: &str,
+        evidence: &Evidence,
+    ) -> Result<Distribution, CognitionError> {
+        let target = self.variable(variable)?;
+        let mut observed = self.observed(evidence)?;
+        // An observed target keeps its factors so the evidence is still
+        // checked; the result is then a point mass on the observation
+        let target_value = observed
+            .iter()
+            .position(|&(o, _)| o == target)
+            .map(|i| observed.swap_remove(i).1);
+
+        let mut factors: Vec<Factor> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, _)| {
+                let mut factor = Factor::from_node(self, i);
+                for &(var, value) in &observed {
+                    factor = factor.reduce(var, value);
+                }
+                factor
+            })
+            .collect();
+
+        let mut hidden: Vec<usize> = (0..self.nodes.len())
+            .filter(|&v| v != target && !observed.iter().any(|&(o, _)| o == v))
+            .collect();
+        while !hidden.is_empty() {
+            // Greedy order: eliminate the variable producing the smallest factor
+            let (position, _) = hidden
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| (i, Self::elimination_cost(&factors, v)))
+                .min_by_key(|&(_, cost)| cost)
+                .expect("hidden is not empty");
+            let var = hidden.swap_remove(position);
+            let (touching, rest): (Vec<Factor>, Vec<Factor>) =
+                factors.into_iter().partition(|f| f.vars.contains(&var));
+            factors = rest;
+            if let Some(product) = touching.into_iter().reduce(|a, b| a.product(&b)) {
+                factors.push(product.sum_out(var));
+            }
+        }
+
+        let joint = factors
+            .into_iter()
+            .reduce(|a, b| a.product(&b))
+            .expect("network has at least the target node");
+        let node = &self.nodes[target];
+        let probabilities = (0..node.states.len())
+            .map(|state| match target_value {
+                Some(value) if value != state => 0.0,
+                _ => joint.value_of(target, state),
+            })
+            .collect();
+        self.distribution(target, probabilities)
+    }
+
+    /// Approximate posterior by likelihood weighting
+    pub fn likelihood_weighting(
+        &self,
+        variable: &str,
+        evidence: &Evidence,
+        samples: usize,
+        seed: u64,
+    ) -> Result<Distribution, CognitionError> {
+        let target = self.variable(variable)?;
+        let observed = self.observed(evidence)?;
+        let mut rng = SplitMix64::new(seed);
+        let mut totals = vec![0.0; self.nodes[target].states.len()];
+        let mut assignment = vec![0; self.nodes.len()];
+        for _ in 0..samples {
+            let mut weight = 1.0;
+            for (i, node) in self.nodes.iter().enumerate() {
+                let row = &node.table[self.row(i, &assignment)];
+                match observed.iter().find(|&&(o, _)| o == i) {
+                    Some(&(_, value)) => {
+                        assignment[i] = value;
+                        weight *= row[value];
+                    }
+                    None => assignment[i] = rng.choose(row),
+                }
+            }
+            totals[assignment[target]] += weight;
+        }
+        self.distribution(target, totals)
+    }
+
+    /// Approximate posterior by Gibbs sampling
+    ///
+    /// The first `burn_in` sweeps are discarded; each of the following
+    /// `samples` sweeps resamples every unobserved variable once.
+    pub fn gibbs(
+        &self,
+        variable: &str,
+        evidence: &Evidence,
+        samples: usize,
+        burn_in: usize,
+        seed: u64,
+    ) -> Result<Distribution, CognitionError> {
+        let target = self.variable(variable)?;
+        let observed = self.observed(evidence)?;
+        let mut rng = SplitMix64::new(seed);
+        let children: Vec<Vec<usize>> = (0..self.nodes.len())
+            .map(|i| {
+                (0..self.nodes.len())
+                    .filter(|&c| self.nodes[c].parents.contains(&i))
+                    .collect()
+            })
+            .collect();
+
+        // Start from a forward sample consistent with the evidence
+        let mut assignment = vec![0; self.nodes.len()];
+        for i in 0..self.nodes.len() {
+            assignment[i] = match observed.iter().find(|&&(o, _)| o == i) {
+                Some(&(_, value)) => value,
+                None => rng.choose(&self.nodes[i].table[self.row(i, &assignment)]),
+            };
+        }
+        let free: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| !observed.iter().any(|&(o, _)| o == i))
+            .collect();
+
+        let mut counts = vec![0.0; self.nodes[target].states.len()];
+        for sweep in 0..burn_in + samples {
+            for &i in &free {
+                let weights: Vec<f64> = (0..self.nodes[i].states.len())
+                    .map(|state| {
+                        assignment[i] = state;
+                        let own = self.nodes[i].table[self.row(i, &assignment)][state];
+                        children[i].iter().fold(own, |p, &c| {
+                            p * self.nodes[c].table[self.row(c, &assignment)][assignment[c]]
+                        })
+                    })
+                    .collect();
+                let total: f64 = weights.iter().sum();
+                if total == 0.0 {
+                    return Err(Self::error("evidence has zero probability".to_string()));
+                }
+                let normalized: Vec<f64> = weights.iter().map(|w| w / total).collect();
+                assignment[i] = rng.choose(&normalized);
+            }
+            if sweep >= burn_in {
+                counts[assignment[target]] += 1.0;
+            }
+        }
+        self.distribution(target, counts)
+    }
+
+    /// Exact posterior of every unobserved variable, as beliefs
+    ///
+    /// Evidence is read from the belief base.
+    pub fn posterior_beliefs(&self, beliefs: &BeliefBase) -> Result<Vec<Belief>, CognitionError> {
+        let evidence = self.evidence_from(beliefs);
+        self.nodes
+            .iter()
+            .filter(|node| !evidence.contains_key(&node.name))
+            .map(|node| self.query(&node.name, &evidence).map(|d| d.to_belief()))
+            .collect()
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    fn variable(&self, name: &str) -> Result<usize, CognitionError> {
+        self.index(name)
+            .ok_or_else(|| Self::error(format!("unknown variable '{}'", name)))
+    }
+
+    /// Evidence as (variable, state) indices
+    fn observed(&self, evidence: &Evidence) -> Result<Vec<(usize, usize)>, CognitionError> {
+        evidence
+            .iter()
+            .map(|(name, state)| {
+                let var = self.variable(name)?;
+                let value = self.nodes[var]
+                    .states
+                    .iter()
+                    .position(|s| s == state)
+                    .ok_or_else(|| Self::error(format!("'{}' has no state '{}'", name, state)))?;
+                Ok((var, value))
+            })
+            .collect()
+    }
+
+    /// Table row of a node for the parent states in `assignment`
+    fn row(&self, node: usize, assignment: &[usize]) -> usize {
+        self.nodes[node].parents.iter().fold(0, |row, &p| {
+            row * self.nodes[p].states.len() + assignment[p]
+        })
+    }
+
+    fn elimination_cost(factors: &[Factor], var: usize) -> usize {
+        let mut vars: Vec<usize> = factors
+            .iter()
+            .filter(|f| f.vars.contains(&var))
+            .flat_map(|f| f.vars.iter().copied())
+            .collect();
+        vars.sort_unstable();
+        vars.dedup();
+        vars.len()
+    }
+
+    fn distribution(
+        &self,
+        target: usize,
+        weights: Vec<f64>,
+    ) -> Result<Distribution, CognitionError> {
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(Self::error("evidence has zero probability".to_string()));
+        }
+        let node = &self.nodes[target];
+        Ok(Distribution {
+            variable: node.name.clone(),
+            states: node.states.clone(),
+            probabilities: weights.iter().map(|w| w / total).collect(),
+        })
+    }
+
+    fn error(message: String) -> CognitionError {
+        CognitionError::ReasoningError(message)
+    }
+}
+
+/// Table over a set of variables, stored with the last variable varying fastest
+#[derive(Debug, Clone)]
+struct Factor {
+    vars: Vec<usize>,
+    cards: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl Factor {
+    fn from_node(network: &BayesianNetwork, index: usize) -> Self {
+        let node = &network.nodes[index];
+        let mut vars = node.parents.clone();
+        vars.push(index);
+        let cards = vars
+            .iter()
+            .map(|&v| network.nodes[v].states.len())
+            .collect();
+        Self {
+            vars,
+            cards,
+            values: node.table.iter().flatten().copied().collect(),
+        }
+    }
+
+    /// Assignment of every variable for a flat position
+    fn assignment(&self, mut position: usize) -> Vec<usize> {
+        let mut assignment = vec![0; self.vars.len()];
+        for i in (0..self.vars.len()).rev() {
+            assignment[i] = position % self.cards[i];
+            position /= self.cards[i];
+        }
+        assignment
+    }
+
+    fn position(&self, lookup: impl Fn(usize) -> usize) -> usize {
+        self.vars
+            .iter()
+            .zip(&self.cards)
+            .fold(0, |position, (&var, &card)| position * card + lookup(var))
+    }
+
+    fn product(&self, other: &Factor) -> Factor {
+        let mut vars = self.vars.clone();
+        let mut cards = self.cards.clone();
+        for (&var, &card) in other.vars.iter().zip(&other.cards) {
+            if !vars.contains(&var) {
+                vars.push(var);
+                cards.push(card);
+            }
+        }
+        let mut result = Factor {
+            values: vec![0.0; cards.iter().product()],
+            vars,
+            cards,
+        };
+        for position in 0..result.values.len() {
+            let assignment = result.assignment(position);
+            let lookup = |var: usize| {
+                let i = result
+                    .vars
+                    .iter()
+                    .position(|&v| v == var)
+                    .expect("var in union");
+                assignment[i]
+            };
+            result.values[position] =
+                self.values[self.position(lookup)] * other.values[other.position(lookup)];
+        }
+        result
+    }
+
+    fn sum_out(&self, var: usize) -> Factor {
+        self.collapse(var, None)
+    }
+
+    fn reduce(&self, var: usize, value: usize) -> Factor {
+        self.collapse(var, Some(value))
+    }
+
+    /// Drop `var`, keeping only `value` if given or summing over it otherwise
+    fn collapse(&self, var: usize, value: Option<usize>) -> Factor {
+        let Some(index) = self.vars.iter().position(|&v| v == var) else {
+            return self.clone();
+        };
+        let mut vars = self.vars.clone();
+        let mut cards = self.cards.clone();
+        vars.remove(index);
+        cards.remove(index);
+        let mut result = Factor {
+            values: vec![0.0; cards.iter().product()],
+            vars,
+            cards,
+        };
+        for (position, &p) in self.values.iter().enumerate() {
+            let mut assignment = self.assignment(position);
+            if value.is_some_and(|v| assignment[index] != v) {
+                continue;
+            }
+            assignment.remove(index);
+            let target = assignment
+                .iter()
+                .zip(&result.cards)
+                .fold(0, |t, (&a, &card)| t * card + a);
+            result.values[target] += p;
+        }
+        result
+    }
+
+    /// Unnormalized weight of `var = state` in a factor over just `var`
+    fn value_of(&self, var: usize, state: usize) -> f64 {
+        self.values[self.position(|v| if v == var { state } else { 0 })]
+    }
+}
+
+/// Small seeded generator (SplitMix64) so sampling is reproducible
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Index drawn from a distribution
+    fn choose(&mut self, distribution: &[f64]) -> usize {
+        let mut u = self.next_f64();
+        for (i, &p) in distribution.iter().enumerate() {
+            if u < p {
+                return i;
+            }
+            u -= p;
+        }
+        distribution.len() - 1
+    }
+}
@@ -0,0 +1,90 @@
+use super::{Substitution, Term};
+use std::cmp::Ordering;
+
+/// Comparison operators usable as rule conditions, e.g. `X > 25`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// Operators, longest first so `>=` is tried before `>`
+    pub(crate) const ALL: [Comparison; 6] = [
+        Comparison::LessEqual,
+        Comparison::GreaterEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::Less,
+        Comparison::Greater,
+    ];
+
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        }
+    }
+
+    pub(crate) fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.symbol() == symbol)
+    }
+
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessEqual => ordering.is_le(),
+            Comparison::Greater => ordering.is_gt(),
+            Comparison::GreaterEqual => ordering.is_ge(),
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::NotEqual => ordering.is_ne(),
+        }
+    }
+}
+
+/// The comparison a term denotes, if it is one
+pub(crate) fn comparison(term: &Term) -> Option<(Comparison, &Term, &Term)> {
+    match term {
+        Term::Compound(functor, args) if args.len() == 2 => {
+            Comparison::from_symbol(functor).map(|c| (c, &args[0], &args[1]))
+        }
+        _ => None,
+    }
+}
+
+/// Check if a condition is evaluated rather than matched against facts
+pub(crate) fn is_builtin(term: &Term) -> bool {
+    comparison(term).is_some()
+}
+
+/// Evaluate a built-in condition under the given bindings
+///
+/// Numbers compare numerically and other ground terms by their text;
+/// comparisons with unbound variables never hold.
+pub(crate) fn holds(term: &Term, bindings: &Substitution) -> bool {
+    let Some((op, left, right)) = comparison(term) else {
+        return false;
+    };
+    let (left, right) = (bindings.apply(left), bindings.apply(right));
+    if !left.is_ground() || !right.is_ground() {
+        return false;
+    }
+    let ordering = match (left.as_number(), right.as_number()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ if matches!(op, Comparison::Equal | Comparison::NotEqual) => Some(if left == right {
+            Ordering::Equal
+        } else {
+            Ordering::Less
+        }),
+        _ => Some(left.to_string().cmp(&right.to_string())),
+    };
+    ordering.is_some_and(|o| op.accepts(o))
+}
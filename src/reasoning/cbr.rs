@@ -0,0 +1,314 @@
+use crate::planning::{Action, Plan, State};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Maps a retrieved case's plan onto a new problem
+pub type Adaptation = Arc<dyn Fn(&Case, &State) -> Plan + Send + Sync>;
+
+/// How executing a case's plan turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The plan achieved its goal
+    Success,
+    /// The plan failed
+    Failure,
+}
+
+/// Past problem, the plan used to solve it and how it turned out
+#[derive(Debug, Clone)]
+pub struct Case {
+    problem: State,
+    solution: Plan,
+    outcome: Outcome,
+    reuses: usize,
+}
+
+impl Case {
+    /// Create a new case
+    pub fn new(problem: State, solution: Plan, outcome: Outcome) -> Self {
+        Self {
+            problem,
+            solution,
+            outcome,
+            reuses: 0,
+        }
+    }
+
+    /// Get the problem
+    pub fn problem(&self) -> &State {
+        &self.problem
+    }
+
+    /// Get the solution
+    pub fn solution(&self) -> &Plan {
+        &self.solution
+    }
+
+    /// Get the outcome
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Get how often the case was reused
+    pub fn reuses(&self) -> usize {
+        self.reuses
+    }
+}
+
+/// Weighted similarity between problem states
+///
+/// Each feature of the query contributes its weight (1.0 unless set) times
+/// a local similarity: numbers score `1 - |a - b| / range` when a range is
+/// set and `1 / (1 + |a - b|)` otherwise; other values score 1 when equal.
+/// Features missing from the case score 0.
+#[derive(Debug, Clone, Default)]
+pub struct Similarity {
+    weights: HashMap<String, f64>,
+    ranges: HashMap<String, f64>,
+}
+
+impl Similarity {
+    /// Create a similarity weighting every feature equally
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the weight of a feature; zero ignores it
+    pub fn with_weight(mut self, feature: impl Into<String>, weight: f64) -> Self {
+        self.weights.insert(feature.into(), weight.max(0.0));
+        self
+    }
+
+    /// Set the range of a numeric feature
+    pub fn with_range(mut self, feature: impl Into<String>, min: f64, max: f64) -> Self {
+        self.ranges.insert(feature.into(), (max - min).abs());
+        self
+    }
+
+    /// Similarity of a case's problem to a query, from 0.0 to 1.0
+    pub fn compare(&self, query: &State, case: &State) -> f64 {
+        let mut total = 0.0;
+        let mut weights = 0.0;
+        for (feature, value) in query.variables() {
+            let weight = self.weights.get(feature).copied().unwrap_or(1.0);
+            if weight == 0.0 {
+                continue;
+            }
+            weights += weight;
+            if let Some(other) = case.get(feature) {
+                total += weight * self.local(feature, value, other);
+            }
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    }
+
+    fn local(&self, feature: &str, a: &str, b: &str) -> f64 {
+        match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => {
+                let distance = (a - b).abs();
+                match self.ranges.get(feature) {
+                    Some(&range) if range > 0.0 => (1.0 - distance / range).max(0.0),
+                    _ => 1.0 / (1.0 + distance),
+                }
+            }
+            _ => f64::from(u8::from(a == b)),
+        }
+    }
+}
+
+/// Which cases a case base keeps
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Retention {
+    /// Keep every case
+    All,
+    /// Keep only successful cases
+    #[default]
+    SuccessfulOnly,
+    /// Keep successful cases unless one at least this similar is known
+    Novel(f64),
+}
+
+/// Plan adaptation substituting new problem values for old ones
+///
+/// Every action parameter equal to a feature value of the case's problem is
+/// replaced by that feature's value in the new problem.
+pub fn substitute_parameters(case: &Case, problem: &State) -> Plan {
+    let replacements: HashMap<&str, &str> = case
+        .problem()
+        .variables()
+        .iter()
+        .filter_map(|(feature, old)| Some((old.as_str(), problem.get(feature)?.as_str())))
+        .collect();
+    case.solution()
+        .actions()
+        .iter()
+        .fold(Plan::new(case.solution().name()), |plan, action| {
+            let adapted = action.parameters().iter().fold(
+                Action::new(action.name()),
+                |adapted, parameter| {
+                    let parameter = replacements
+                        .get(parameter.as_str())
+                        .map_or(parameter.as_str(), |new| new);
+                    adapted.with_parameter(parameter)
+                },
+            );
+            plan.add_action(adapted)
+        })
+}
+
+/// Case memory for reusing past plans
+///
+/// Retrieval ranks cases by similarity (k-nearest neighbours); reuse adapts
+/// the plan of the most similar successful case to the new problem.
+#[derive(Clone)]
+pub struct CaseBase {
+    cases: Vec<Case>,
+    similarity: Similarity,
+    retention: Retention,
+    capacity: Option<usize>,
+    threshold: f64,
+    adaptation: Adaptation,
+}
+
+impl fmt::Debug for CaseBase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CaseBase")
+            .field("cases", &self.cases)
+            .field("similarity", &self.similarity)
+            .field("retention", &self.retention)
+            .field("capacity", &self.capacity)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl Default for CaseBase {
+    fn default() -> Self {
+        Self::new(Similarity::default())
+    }
+}
+
+impl CaseBase {
+    /// Create an empty case base
+    pub fn new(similarity: Similarity) -> Self {
+        Self {
+            cases: Vec::new(),
+            similarity,
+            retention: Retention::default(),
+            capacity: None,
+            threshold: 0.0,
+            adaptation: Arc::new(substitute_parameters),
+        }
+    }
+
+    /// Set the retention policy
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Keep at most `capacity` cases, evicting the least reused (oldest first)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Only reuse cases at least this similar to the problem
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Replace the plan adaptation (default: `substitute_parameters`)
+    pub fn with_adaptation(
+        mut self,
+        adaptation: impl Fn(&Case, &State) -> Plan + Send + Sync + 'static,
+    ) -> Self {
+        self.adaptation = Arc::new(adaptation);
+        self
+    }
+
+    /// Get all cases
+    pub fn cases(&self) -> &[Case] {
+        &self.cases
+    }
+
+    /// Number of cases
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    /// Store a case if the retention policy keeps it; returns whether it did
+    pub fn retain(&mut self, case: Case) -> bool {
+        let keep = match self.retention {
+            Retention::All => true,
+            Retention::SuccessfulOnly => case.outcome == Outcome::Success,
+            Retention::Novel(threshold) => {
+                case.outcome == Outcome::Success
+                    && self
+                        .cases
+                        .iter()
+                        .all(|c| self.similarity.compare(&case.problem, &c.problem) < threshold)
+            }
+        };
+        if !keep || self.capacity == Some(0) {
+            return false;
+        }
+        if self
+            .capacity
+            .is_some_and(|capacity| self.cases.len() >= capacity)
+        {
+            let evicted = self
+                .cases
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, c)| c.reuses)
+                .map(|(i, _)| i)
+                .expect("case base is full");
+            self.cases.remove(evicted);
+        }
+        self.cases.push(case);
+        true
+    }
+
+    /// The `k` cases most similar to a problem, most similar first
+    pub fn retrieve(&self, problem: &State, k: usize) -> Vec<(f64, &Case)> {
+        let mut ranked: Vec<(f64, &Case)> = self
+            .cases
+            .iter()
+            .map(|c| (self.similarity.compare(problem, &c.problem), c))
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.truncate(k);
+        ranked
+    }
+
+    /// Adapt the plan of the most similar successful case to a problem
+    ///
+    /// Returns `None` when no successful case reaches the threshold, in which
+    /// case the agent should plan from scratch.
+    pub fn reuse(&mut self, problem: &State) -> Option<Plan> {
+        let (similarity, index) = self
+            .cases
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.outcome == Outcome::Success)
+            .map(|(i, c)| (self.similarity.compare(problem, &c.problem), i))
+            .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)))?;
+        if similarity < self.threshold {
+            return None;
+        }
+        self.cases[index].reuses += 1;
+        Some((self.adaptation)(&self.cases[index], problem))
+    }
+}
@@ -0,0 +1,27 @@
+/// How the certainties of a rule's conditions combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CertaintyCombination {
+    /// The weakest condition bounds the match (fuzzy AND)
+    #[default]
+    Min,
+    /// Conditions are treated as independent evidence
+    Product,
+}
+
+impl CertaintyCombination {
+    /// Certainty of all conditions holding together
+    pub fn conjunction(&self, certainties: impl IntoIterator<Item = f64>) -> f64 {
+        match self {
+            CertaintyCombination::Min => certainties.into_iter().fold(1.0, f64::min),
+            CertaintyCombination::Product => certainties.into_iter().product(),
+        }
+    }
+}
+
+/// MYCIN combination of two certainties supporting the same conclusion
+///
+/// Each derivation closes part of the remaining gap to certainty, so the
+/// result never exceeds 1.0 and does not depend on the order of evidence.
+pub fn combine_evidence(a: f64, b: f64) -> f64 {
+    a + b * (1.0 - a)
+}
@@ -0,0 +1,353 @@
+use crate::planning::State;
+use crate::CognitionError;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Relation a binary constraint requires between two values
+pub type Relation = Arc<dyn Fn(&str, &str) -> bool + Send + Sync>;
+
+/// Bound on the sum of integer variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SumBound {
+    /// Sum equals the value
+    Equal(i64),
+    /// Sum is at most the value
+    AtMost(i64),
+    /// Sum is at least the value
+    AtLeast(i64),
+}
+
+impl SumBound {
+    fn range(self) -> (i64, i64) {
+        match self {
+            SumBound::Equal(n) => (n, n),
+            SumBound::AtMost(n) => (i64::MIN, n),
+            SumBound::AtLeast(n) => (n, i64::MAX),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Constraint {
+    Binary(usize, usize, Relation),
+    AllDifferent(Vec<usize>),
+    Sum(Vec<usize>, SumBound),
+}
+
+impl Constraint {
+    fn involves(&self, var: usize) -> bool {
+        match self {
+            Constraint::Binary(x, y, _) => *x == var || *y == var,
+            Constraint::AllDifferent(vars) | Constraint::Sum(vars, _) => vars.contains(&var),
+        }
+    }
+}
+
+/// Finite-domain constraint satisfaction problem
+///
+/// Solving first makes every binary constraint arc consistent (AC-3, with
+/// all-different taken pairwise), then backtracks choosing the variable with
+/// the fewest remaining values (MRV) and trying its least constraining
+/// values first (LCV). Each assignment is forward checked: binary and
+/// all-different constraints prune the domains of unassigned variables and
+/// sum constraints prune values outside their bounds.
+#[derive(Clone, Default)]
+pub struct Csp {
+    variables: Vec<String>,
+    domains: Vec<Vec<String>>,
+    constraints: Vec<Constraint>,
+}
+
+impl fmt::Debug for Csp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Csp")
+            .field("variables", &self.variables)
+            .field("domains", &self.domains)
+            .field("constraints", &self.constraints.len())
+            .finish()
+    }
+}
+
+impl Csp {
+    /// Create an empty problem
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a variable with its possible values
+    pub fn add_variable<V: Into<String>>(
+        &mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<(), CognitionError> {
+        let name = name.into();
+        if self.variables.contains(&name) {
+            return Err(CognitionError::ReasoningError(format!(
+                "duplicate variable '{}'",
+                name
+            )));
+        }
+        let mut domain: Vec<String> = Vec::new();
+        for value in values {
+            let value = value.into();
+            if !domain.contains(&value) {
+                domain.push(value);
+            }
+        }
+        self.variables.push(name);
+        self.domains.push(domain);
+        Ok(())
+    }
+
+    /// Add a variable ranging over consecutive integers
+    pub fn add_range(
+        &mut self,
+        name: impl Into<String>,
+        range: RangeInclusive<i64>,
+    ) -> Result<(), CognitionError> {
+        self.add_variable(name, range.map(|n| n.to_string()))
+    }
+
+    /// Require `relation` to hold between the values of two variables
+    pub fn add_constraint(
+        &mut self,
+        a: &str,
+        b: &str,
+        relation: impl Fn(&str, &str) -> bool + Send + Sync + 'static,
+    ) -> Result<(), CognitionError> {
+        let (x, y) = (self.index(a)?, self.index(b)?);
+        self.constraints
+            .push(Constraint::Binary(x, y, Arc::new(relation)));
+        Ok(())
+    }
+
+    /// Require the variables to take pairwise different values
+    pub fn add_all_different(&mut self, variables: &[&str]) -> Result<(), CognitionError> {
+        let vars = self.indices(variables)?;
+        self.constraints.push(Constraint::AllDifferent(vars));
+        Ok(())
+    }
+
+    /// Bound the sum of integer variables
+    pub fn add_sum(&mut self, variables: &[&str], bound: SumBound) -> Result<(), CognitionError> {
+        let vars = self.indices(variables)?;
+        for &var in &vars {
+            if let Some(value) = self.domains[var].iter().find(|v| v.parse::<i64>().is_err()) {
+                return Err(CognitionError::ReasoningError(format!(
+                    "sum over '{}' needs integer values, found '{}'",
+                    self.variables[var], value
+                )));
+            }
+        }
+        self.constraints.push(Constraint::Sum(vars, bound));
+        Ok(())
+    }
+
+    /// Get the variable names
+    pub fn variables(&self) -> Vec<&str> {
+        self.variables.iter().map(String::as_str).collect()
+    }
+
+    /// Get the remaining values of a variable
+    pub fn domain(&self, variable: &str) -> Option<&[String]> {
+        let index = self.variables.iter().position(|v| v == variable)?;
+        Some(&self.domains[index])
+    }
+
+    /// Narrow domains to arc consistency; false if some domain became empty
+    pub fn propagate(&mut self) -> bool {
+        let mut domains = std::mem::take(&mut self.domains);
+        let consistent = self.arc_consistency(&mut domains);
+        self.domains = domains;
+        consistent
+    }
+
+    /// Find one solution
+    pub fn solve(&self) -> Option<State> {
+        self.solutions(1).into_iter().next()
+    }
+
+    /// Find up to `limit` solutions
+    pub fn solutions(&self, limit: usize) -> Vec<State> {
+        let mut solutions = Vec::new();
+        let mut domains = self.domains.clone();
+        if limit == 0 || !self.arc_consistency(&mut domains) || !self.bound_sums(&mut domains) {
+            return solutions;
+        }
+        let mut assigned = vec![false; self.variables.len()];
+        self.search(domains, &mut assigned, limit, &mut solutions);
+        solutions
+    }
+
+    fn index(&self, name: &str) -> Result<usize, CognitionError> {
+        self.variables
+            .iter()
+            .position(|v| v == name)
+            .ok_or_else(|| CognitionError::ReasoningError(format!("unknown variable '{}'", name)))
+    }
+
+    fn indices(&self, names: &[&str]) -> Result<Vec<usize>, CognitionError> {
+        names.iter().map(|name| self.index(name)).collect()
+    }
+
+    /// Binary views of the constraints as (x, y, constraint index)
+    fn arcs(&self) -> Vec<(usize, usize, usize)> {
+        let mut arcs = Vec::new();
+        for (c, constraint) in self.constraints.iter().enumerate() {
+            match constraint {
+                Constraint::Binary(x, y, _) => {
+                    arcs.push((*x, *y, c));
+                    arcs.push((*y, *x, c));
+                }
+                Constraint::AllDifferent(vars) => {
+                    for &x in vars {
+                        for &y in vars.iter().filter(|&&y| y != x) {
+                            arcs.push((x, y, c));
+                        }
+                    }
+                }
+                Constraint::Sum(..) => {}
+            }
+        }
+        arcs
+    }
+
+    /// Check a value of `x` against a value of `y` under a binary view
+    fn allows(&self, constraint: usize, x: usize, vx: &str, vy: &str) -> bool {
+        match &self.constraints[constraint] {
+            Constraint::Binary(a, _, relation) if *a == x => relation(vx, vy),
+            Constraint::Binary(_, _, relation) => relation(vy, vx),
+            Constraint::AllDifferent(_) => vx != vy,
+            Constraint::Sum(..) => true,
+        }
+    }
+
+    /// AC-3 over the binary views
+    fn arc_consistency(&self, domains: &mut [Vec<String>]) -> bool {
+        let arcs = self.arcs();
+        let mut queue: VecDeque<(usize, usize, usize)> = arcs.iter().copied().collect();
+        while let Some((x, y, c)) = queue.pop_front() {
+            let before = domains[x].len();
+            let support = domains[y].clone();
+            domains[x].retain(|vx| support.iter().any(|vy| self.allows(c, x, vx, vy)));
+            if domains[x].is_empty() {
+                return false;
+            }
+            if domains[x].len() < before {
+                queue.extend(arcs.iter().filter(|&&(_, to, d)| to == x && d != c));
+            }
+        }
+        true
+    }
+
+    /// Remove values no assignment within the sum bounds can use
+    fn bound_sums(&self, domains: &mut [Vec<String>]) -> bool {
+        let number = |v: &String| v.parse::<i64>().unwrap_or_default();
+        loop {
+            let mut changed = false;
+            for constraint in &self.constraints {
+                let Constraint::Sum(vars, bound) = constraint else {
+                    continue;
+                };
+                let (lo, hi) = bound.range();
+                let min: i64 = vars
+                    .iter()
+                    .map(|&v| domains[v].iter().map(number).min().unwrap_or_default())
+                    .sum();
+                let max: i64 = vars
+                    .iter()
+                    .map(|&v| domains[v].iter().map(number).max().unwrap_or_default())
+                    .sum();
+                for &var in vars {
+                    let own_min = domains[var].iter().map(number).min().unwrap_or_default();
+                    let own_max = domains[var].iter().map(number).max().unwrap_or_default();
+                    let before = domains[var].len();
+                    domains[var].retain(|value| {
+                        let n = number(value);
+                        min - own_min + n <= hi && max - own_max + n >= lo
+                    });
+                    if domains[var].is_empty() {
+                        return false;
+                    }
+                    changed |= domains[var].len() < before;
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    /// Prune unassigned domains after assigning `value` to `var`
+    fn forward_check(
+        &self,
+        domains: &mut [Vec<String>],
+        assigned: &[bool],
+        var: usize,
+        value: &str,
+    ) -> bool {
+        domains[var] = vec![value.to_string()];
+        for (x, y, c) in self.arcs() {
+            if y != var || assigned[x] {
+                continue;
+            }
+            domains[x].retain(|vx| self.allows(c, x, vx, value));
+            if domains[x].is_empty() {
+                return false;
+            }
+        }
+        self.bound_sums(domains)
+    }
+
+    fn search(
+        &self,
+        domains: Vec<Vec<String>>,
+        assigned: &mut Vec<bool>,
+        limit: usize,
+        solutions: &mut Vec<State>,
+    ) {
+        // Minimum remaining values, ties broken by most constraints
+        let Some(var) = (0..self.variables.len())
+            .filter(|&v| !assigned[v])
+            .min_by_key(|&v| {
+                let degree = self.constraints.iter().filter(|c| c.involves(v)).count();
+                (domains[v].len(), std::cmp::Reverse(degree))
+            })
+        else {
+            let state = self
+                .variables
+                .iter()
+                .zip(&domains)
+                .fold(State::new(), |state, (name, domain)| {
+                    state.set(name.clone(), domain[0].clone())
+                });
+            solutions.push(state);
+            return;
+        };
+
+        // Least constraining value: keep the most options for the rest
+        let mut candidates: Vec<(usize, Vec<Vec<String>>)> = domains[var]
+            .iter()
+            .filter_map(|value| {
+                let mut pruned = domains.clone();
+                if !self.forward_check(&mut pruned, assigned, var, value) {
+                    return None;
+                }
+                let remaining = pruned.iter().map(Vec::len).sum();
+                Some((remaining, pruned))
+            })
+            .collect();
+        candidates.sort_by_key(|(remaining, _)| std::cmp::Reverse(*remaining));
+
+        assigned[var] = true;
+        for (_, pruned) in candidates {
+            self.search(pruned, assigned, limit, solutions);
+            if solutions.len() >= limit {
+                break;
+            }
+        }
+        assigned[var] = false;
+    }
+}
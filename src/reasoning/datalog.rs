@@ -0,0 +1,561 @@
+use super::builtin;
+use super::{unify, Rule, Substitution, Term};
+use crate::bdi::BeliefBase;
+use crate::CognitionError;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Aggregate function usable as a conclusion argument, e.g. `total(D, sum(S))`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// Number of distinct values of a variable
+    Count,
+    /// Sum of a numeric variable
+    Sum,
+    /// Smallest value of a numeric variable
+    Min,
+    /// Largest value of a numeric variable
+    Max,
+}
+
+impl Aggregate {
+    fn from_functor(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(Aggregate::Count),
+            "sum" => Some(Aggregate::Sum),
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            _ => None,
+        }
+    }
+}
+
+/// Body literal of a Datalog rule
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Positive(Term),
+    Negative(Term),
+    Builtin(Term),
+}
+
+impl Literal {
+    /// Parse a condition; `not p(X)` and `\+ p(X)` are negated
+    fn parse(text: &str) -> Result<Self, CognitionError> {
+        let text = text.trim();
+        let negated = text
+            .strip_prefix("not ")
+            .or_else(|| text.strip_prefix("\\+"));
+        match negated {
+            Some(rest) => Ok(Literal::Negative(Term::parse(rest)?)),
+            None => {
+                let term = Term::parse(text)?;
+                if builtin::is_builtin(&term) {
+                    Ok(Literal::Builtin(term))
+                } else {
+                    Ok(Literal::Positive(term))
+                }
+            }
+        }
+    }
+
+    fn term(&self) -> &Term {
+        match self {
+            Literal::Positive(term) | Literal::Negative(term) | Literal::Builtin(term) => term,
+        }
+    }
+}
+
+/// Single-conclusion rule prepared for evaluation
+#[derive(Debug, Clone)]
+struct Clause {
+    name: String,
+    head: Term,
+    body: Vec<Literal>,
+    aggregates: Vec<(usize, Aggregate, Term)>,
+}
+
+impl Clause {
+    fn has_aggregate(&self) -> bool {
+        !self.aggregates.is_empty()
+    }
+}
+
+type Predicate = (String, usize);
+
+fn predicate(term: &Term) -> Result<Predicate, CognitionError> {
+    term.functor()
+        .map(|(name, arity)| (name.to_string(), arity))
+        .ok_or_else(|| {
+            CognitionError::ReasoningError(format!("'{}' is not a valid Datalog atom", term))
+        })
+}
+
+/// Declarative Datalog program over `Rule`s and ground facts
+///
+/// Conditions may be negated with `not` or be comparisons such as `X > 25`;
+/// conclusion arguments may be `count(X)`, `sum(X)`, `min(X)` or `max(X)`,
+/// grouping by the remaining arguments. Negation and aggregation must be
+/// stratified.
+#[derive(Debug, Clone, Default)]
+pub struct DatalogProgram {
+    rules: Vec<Rule>,
+    facts: Vec<Term>,
+}
+
+impl DatalogProgram {
+    /// Create an empty program
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Add a ground fact such as `edge(a, b)`
+    pub fn add_fact(&mut self, fact: &str) -> Result<(), CognitionError> {
+        let term = Term::parse(fact)?;
+        if !term.is_ground() {
+            return Err(CognitionError::ReasoningError(format!(
+                "fact '{}' is not ground",
+                fact
+            )));
+        }
+        self.facts.push(term);
+        Ok(())
+    }
+
+    /// Add every belief as a fact `key(value)`
+    pub fn add_beliefs(&mut self, beliefs: &BeliefBase) {
+        self.facts.extend(beliefs.all().map(Term::from));
+    }
+
+    /// Get rules
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Evaluate the program bottom-up, stratum by stratum
+    pub fn evaluate(&self) -> Result<DatalogModel, CognitionError> {
+        let clauses = self.clauses()?;
+        let strata = stratify(&clauses)?;
+
+        let mut model = DatalogModel::default();
+        for fact in &self.facts {
+            model.insert(fact.clone());
+        }
+        for stratum in strata {
+            let (aggregates, plain): (Vec<&Clause>, Vec<&Clause>) = stratum
+                .iter()
+                .map(|&i| &clauses[i])
+                .partition(|c| c.has_aggregate());
+            for clause in aggregates {
+                for fact in aggregate(clause, &model)? {
+                    model.insert(fact);
+                }
+            }
+            semi_naive(&plain, &mut model);
+        }
+        Ok(model)
+    }
+
+    fn clauses(&self) -> Result<Vec<Clause>, CognitionError> {
+        let mut clauses = Vec::new();
+        for rule in &self.rules {
+            let body = rule
+                .conditions()
+                .iter()
+                .map(|c| Literal::parse(c))
+                .collect::<Result<Vec<_>, _>>()?;
+            for conclusion in rule.conclusions() {
+                let head = Term::parse(conclusion)?;
+                let aggregates = head
+                    .args()
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, arg)| match arg {
+                        Term::Compound(name, args) if args.len() == 1 => {
+                            Aggregate::from_functor(name).map(|a| (i, a, args[0].clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let clause = Clause {
+                    name: rule.name().to_string(),
+                    head,
+                    body: body.clone(),
+                    aggregates,
+                };
+                check_safety(&clause)?;
+                clauses.push(clause);
+            }
+        }
+        Ok(clauses)
+    }
+}
+
+/// Every head, negated and compared variable must be bound by a positive literal
+fn check_safety(clause: &Clause) -> Result<(), CognitionError> {
+    let bound: HashSet<&str> = clause
+        .body
+        .iter()
+        .filter_map(|l| match l {
+            Literal::Positive(term) => Some(term.variables()),
+            Literal::Negative(_) | Literal::Builtin(_) => None,
+        })
+        .flatten()
+        .collect();
+    let negated = clause.body.iter().filter_map(|l| match l {
+        Literal::Negative(term) | Literal::Builtin(term) => Some(term),
+        Literal::Positive(_) => None,
+    });
+    for term in std::iter::once(&clause.head).chain(negated) {
+        if let Some(var) = term
+            .variables()
+            .into_iter()
+            .find(|v| !bound.contains(v) && !v.starts_with("_G"))
+        {
+            return Err(CognitionError::ReasoningError(format!(
+                "rule '{}' is unsafe: variable {} in '{}' is not bound by a positive condition",
+                clause.name, var, term
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Group clauses into strata; negation and aggregation only look downwards
+fn stratify(clauses: &[Clause]) -> Result<Vec<Vec<usize>>, CognitionError> {
+    // Edges from body predicate to head predicate, flagged when negative
+    let mut edges: Vec<(Predicate, Predicate, bool, usize)> = Vec::new();
+    for (i, clause) in clauses.iter().enumerate() {
+        let head = predicate(&clause.head)?;
+        for literal in clause
+            .body
+            .iter()
+            .filter(|l| !matches!(l, Literal::Builtin(_)))
+        {
+            let negative = matches!(literal, Literal::Negative(_)) || clause.has_aggregate();
+            edges.push((predicate(literal.term())?, head.clone(), negative, i));
+        }
+    }
+
+    let mut stratum: HashMap<Predicate, usize> = HashMap::new();
+    for clause in clauses {
+        stratum.insert(predicate(&clause.head)?, 0);
+    }
+    let limit = stratum.len();
+    loop {
+        let mut changed = false;
+        for (from, to, negative, rule) in &edges {
+            let required = stratum.get(from).copied().unwrap_or(0) + usize::from(*negative);
+            let current = stratum.get(to).copied().unwrap_or(0);
+            if required > current {
+                if required > limit {
+                    return Err(unstratifiable(clauses, &edges, *rule));
+                }
+                stratum.insert(to.clone(), required);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let depth = stratum.values().copied().max().unwrap_or(0);
+    let mut strata = vec![Vec::new(); depth + 1];
+    for (i, clause) in clauses.iter().enumerate() {
+        strata[stratum[&predicate(&clause.head)?]].push(i);
+    }
+    Ok(strata)
+}
+
+/// Describe a cycle of rules that passes through a negative dependency
+fn unstratifiable(
+    clauses: &[Clause],
+    edges: &[(Predicate, Predicate, bool, usize)],
+    seed: usize,
+) -> CognitionError {
+    // Breadth-first search from every negative edge's head back to its body
+    for (from, to, negative, rule) in edges {
+        if !negative {
+            continue;
+        }
+        let mut previous: HashMap<&Predicate, (&Predicate, usize)> = HashMap::new();
+        let mut queue = VecDeque::from([to]);
+        let mut visited = HashSet::from([to]);
+        while let Some(current) = queue.pop_front() {
+            if current == from {
+                let mut cycle = vec![clauses[*rule].name.clone()];
+                let mut node = from;
+                while node != to {
+                    let (prev, via) = previous[node];
+                    cycle.push(clauses[via].name.clone());
+                    node = prev;
+                }
+                cycle.reverse();
+                cycle.push(cycle[0].clone());
+                return CognitionError::ReasoningError(format!(
+                    "program is not stratifiable: negation or aggregation through recursion in rule cycle {}",
+                    cycle.join(" -> ")
+                ));
+            }
+            for (a, b, _, via) in edges {
+                if a == current && visited.insert(b) {
+                    previous.insert(b, (a, *via));
+                    queue.push_back(b);
+                }
+            }
+        }
+    }
+    CognitionError::ReasoningError(format!(
+        "program is not stratifiable at rule '{}'",
+        clauses[seed].name
+    ))
+}
+
+/// Evaluate one stratum's rules to a fixpoint, joining only against new facts
+fn semi_naive(clauses: &[&Clause], model: &mut DatalogModel) {
+    let recursive: HashSet<Predicate> = clauses
+        .iter()
+        .filter_map(|c| predicate(&c.head).ok())
+        .collect();
+
+    // First round: every rule against everything known so far
+    let mut delta = DatalogModel::default();
+    for clause in clauses {
+        for fact in derive(clause, model, None) {
+            if !model.contains_term(&fact) {
+                delta.insert(fact);
+            }
+        }
+    }
+
+    while !delta.is_empty() {
+        for fact in delta.facts() {
+            model.insert(fact.clone());
+        }
+        let mut next = DatalogModel::default();
+        for clause in clauses {
+            for (i, literal) in clause.body.iter().enumerate() {
+                let Literal::Positive(term) = literal else {
+                    continue;
+                };
+                if !predicate(term).is_ok_and(|p| recursive.contains(&p)) {
+                    continue;
+                }
+                for fact in derive(clause, model, Some((i, &delta))) {
+                    if !model.contains_term(&fact) {
+                        next.insert(fact);
+                    }
+                }
+            }
+        }
+        delta = next;
+    }
+}
+
+/// Body matches of a clause; literal `delta.0` is matched against `delta.1`
+fn matches(
+    clause: &Clause,
+    model: &DatalogModel,
+    delta: Option<(usize, &DatalogModel)>,
+) -> Vec<Substitution> {
+    let mut partial = vec![Substitution::new()];
+    // Positive literals first so comparisons and negations are fully bound
+    let order = clause
+        .body
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| matches!(l, Literal::Positive(_)))
+        .chain(
+            clause
+                .body
+                .iter()
+                .enumerate()
+                .filter(|(_, l)| !matches!(l, Literal::Positive(_))),
+        );
+    for (i, literal) in order {
+        partial = match literal {
+            Literal::Positive(term) => {
+                let source = match delta {
+                    Some((position, facts)) if position == i => facts,
+                    _ => model,
+                };
+                partial
+                    .iter()
+                    .flat_map(|bindings| {
+                        let pattern = bindings.apply(term);
+                        source
+                            .relation(&pattern)
+                            .filter_map(|fact| unify(&pattern, fact, bindings))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect()
+            }
+            Literal::Builtin(term) => partial
+                .into_iter()
+                .filter(|bindings| builtin::holds(term, bindings))
+                .collect(),
+            Literal::Negative(term) => partial
+                .into_iter()
+                .filter(|bindings| {
+                    let pattern = bindings.apply(term);
+                    !model
+                        .relation(&pattern)
+                        .any(|fact| unify(&pattern, fact, &Substitution::new()).is_some())
+                })
+                .collect(),
+        };
+        if partial.is_empty() {
+            break;
+        }
+    }
+    partial
+}
+
+fn derive(
+    clause: &Clause,
+    model: &DatalogModel,
+    delta: Option<(usize, &DatalogModel)>,
+) -> Vec<Term> {
+    matches(clause, model, delta)
+        .iter()
+        .map(|bindings| bindings.apply(&clause.head))
+        .collect()
+}
+
+/// Evaluate an aggregate clause over its (complete) lower strata
+fn aggregate(clause: &Clause, model: &DatalogModel) -> Result<Vec<Term>, CognitionError> {
+    let aggregated: Vec<usize> = clause.aggregates.iter().map(|(i, _, _)| *i).collect();
+    let body_vars: Vec<String> = clause
+        .body
+        .iter()
+        .flat_map(|l| l.term().variables())
+        .map(str::to_string)
+        .collect();
+
+    // Distinct body tuples per group of non-aggregated head arguments
+    let mut groups: Vec<(Vec<Term>, HashSet<Vec<Term>>, Substitution)> = Vec::new();
+    for bindings in matches(clause, model, None) {
+        let key: Vec<Term> = clause
+            .head
+            .args()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !aggregated.contains(i))
+            .map(|(_, arg)| bindings.apply(arg))
+            .collect();
+        let tuple: Vec<Term> = body_vars
+            .iter()
+            .map(|v| bindings.apply(&Term::var(v)))
+            .collect();
+        match groups.iter_mut().find(|(k, _, _)| *k == key) {
+            Some((_, tuples, _)) => {
+                tuples.insert(tuple);
+            }
+            None => groups.push((key, HashSet::from([tuple]), bindings)),
+        }
+    }
+
+    let mut facts = Vec::new();
+    for (_, tuples, sample) in groups {
+        let mut args: Vec<Term> = clause.head.args().iter().map(|a| sample.apply(a)).collect();
+        for (position, function, var) in &clause.aggregates {
+            let index = body_vars.iter().position(|v| Term::var(v) == *var);
+            let values: Vec<f64> = tuples
+                .iter()
+                .filter_map(|t| index.and_then(|i| t[i].as_number()))
+                .collect();
+            let value = match function {
+                Aggregate::Count => match index {
+                    Some(i) => tuples.iter().map(|t| &t[i]).collect::<HashSet<_>>().len() as f64,
+                    None => tuples.len() as f64,
+                },
+                _ if values.len() != tuples.len() => {
+                    return Err(CognitionError::ReasoningError(format!(
+                        "rule '{}' aggregates non-numeric values of {}",
+                        clause.name, var
+                    )))
+                }
+                Aggregate::Sum => values.iter().sum(),
+                Aggregate::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+                Aggregate::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            };
+            args[*position] = number(value);
+        }
+        if let Some((name, _)) = clause.head.functor() {
+            facts.push(Term::compound(name, args));
+        }
+    }
+    Ok(facts)
+}
+
+fn number(value: f64) -> Term {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        Term::atom(format!("{}", value as i64))
+    } else {
+        Term::atom(value.to_string())
+    }
+}
+
+/// Facts derived by evaluating a `DatalogProgram`
+#[derive(Debug, Clone, Default)]
+pub struct DatalogModel {
+    relations: HashMap<Predicate, Vec<Term>>,
+    known: HashSet<Term>,
+}
+
+impl DatalogModel {
+    fn insert(&mut self, fact: Term) {
+        if self.known.contains(&fact) {
+            return;
+        }
+        if let Ok(key) = predicate(&fact) {
+            self.relations.entry(key).or_default().push(fact.clone());
+        }
+        self.known.insert(fact);
+    }
+
+    fn relation<'a>(&'a self, pattern: &Term) -> impl Iterator<Item = &'a Term> + 'a {
+        predicate(pattern)
+            .ok()
+            .and_then(|key| self.relations.get(&key))
+            .into_iter()
+            .flatten()
+    }
+
+    fn contains_term(&self, fact: &Term) -> bool {
+        self.known.contains(fact)
+    }
+
+    /// Check if a ground fact holds
+    pub fn contains(&self, fact: &str) -> bool {
+        Term::parse(fact).is_ok_and(|t| self.known.contains(&t))
+    }
+
+    /// Bindings for every fact matching a pattern such as `path(a, X)`
+    pub fn query(&self, pattern: &str) -> Result<Vec<Substitution>, CognitionError> {
+        let pattern = Term::parse(pattern)?;
+        Ok(self
+            .relation(&pattern)
+            .filter_map(|fact| unify(&pattern, fact, &Substitution::new()))
+            .collect())
+    }
+
+    /// All facts
+    pub fn facts(&self) -> impl Iterator<Item = &Term> {
+        self.known.iter()
+    }
+
+    /// Number of facts
+    pub fn len(&self) -> usize {
+        self.known.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+}
@@ -0,0 +1,244 @@
+use super::builtin;
+use super::{unify, Rule, RuleKind, Substitution, Term};
+use std::collections::HashSet;
+
+/// Conclusions of a rule base under defeasible logic
+///
+/// `neg(p)` is the complement of `p`. A literal is definitely provable when
+/// it follows from the facts by strict rules alone, and defeasibly provable
+/// when some strict or defeasible rule supports it and every rule for its
+/// complement is either inapplicable or beaten by a superior supporting
+/// rule. Ambiguity is blocked: when neither side wins, neither is proved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefeasibleModel {
+    definite: Vec<Term>,
+    defeasible: Vec<Term>,
+    refuted: HashSet<Term>,
+    literals: HashSet<Term>,
+}
+
+impl DefeasibleModel {
+    /// Check if a literal follows from facts and strict rules
+    pub fn is_definite(&self, literal: &str) -> bool {
+        self.definite.contains(&Term::parse_lenient(literal))
+    }
+
+    /// Check if a literal is defeasibly provable
+    pub fn is_defeasible(&self, literal: &str) -> bool {
+        self.defeasible.contains(&Term::parse_lenient(literal))
+    }
+
+    /// Check if a literal is shown not to be defeasibly provable
+    pub fn is_refuted(&self, literal: &str) -> bool {
+        let literal = Term::parse_lenient(literal);
+        self.refuted.contains(&literal) || !self.literals.contains(&literal)
+    }
+
+    /// Get the definitely provable literals
+    pub fn definite(&self) -> &[Term] {
+        &self.definite
+    }
+
+    /// Get the defeasibly provable literals, including definite ones
+    pub fn conclusions(&self) -> &[Term] {
+        &self.defeasible
+    }
+}
+
+/// Ground rule instance
+struct Instance {
+    rule: usize,
+    kind: RuleKind,
+    body: Vec<Term>,
+    head: Term,
+}
+
+/// Compute the defeasible-logic conclusions of facts and rules
+pub(crate) fn evaluate(facts: &[Term], rules: &[Rule]) -> DefeasibleModel {
+    let compiled: Vec<(Vec<Term>, Vec<Term>)> = rules
+        .iter()
+        .map(|r| (r.condition_terms(), r.conclusion_terms()))
+        .collect();
+
+    // Everything any supporting rule could conclude bounds the grounding
+    let mut known: Vec<Term> = Vec::new();
+    for fact in facts {
+        if !known.contains(fact) {
+            known.push(fact.clone());
+        }
+    }
+    let instances = loop {
+        let instances = instantiate(rules, &compiled, &known);
+        let before = known.len();
+        for instance in &instances {
+            if instance.kind != RuleKind::Defeater && !known.contains(&instance.head) {
+                known.push(instance.head.clone());
+            }
+        }
+        if known.len() == before {
+            break instances;
+        }
+    };
+
+    let mut literals: Vec<Term> = Vec::new();
+    let mut seen = HashSet::new();
+    let candidates = known
+        .iter()
+        .chain(instances.iter().map(|i| &i.head))
+        .cloned()
+        .collect::<Vec<_>>();
+    for literal in candidates {
+        for literal in [literal.clone(), complement(&literal)] {
+            if seen.insert(literal.clone()) {
+                literals.push(literal);
+            }
+        }
+    }
+
+    // Definite provability: strict rules over the facts
+    let mut definite: HashSet<Term> = facts.iter().cloned().collect();
+    loop {
+        let before = definite.len();
+        for instance in instances.iter().filter(|i| i.kind == RuleKind::Strict) {
+            if instance.body.iter().all(|b| definite.contains(b)) {
+                definite.insert(instance.head.clone());
+            }
+        }
+        if definite.len() == before {
+            break;
+        }
+    }
+
+    let superior = |t: &Instance, s: &Instance| {
+        rules[t.rule]
+            .superior_to()
+            .iter()
+            .any(|name| name == rules[s.rule].name())
+    };
+    let mut proved: HashSet<Term> = HashSet::new();
+    let mut refuted: HashSet<Term> = HashSet::new();
+    loop {
+        let mut changed = false;
+        for q in &literals {
+            let plus = |a: &Term| proved.contains(a);
+            let minus = |a: &Term| refuted.contains(a) || !seen.contains(a);
+            let negated = complement(q);
+            let provable = !proved.contains(q)
+                && (definite.contains(q)
+                    || (!definite.contains(&negated)
+                        && supporting(&instances, q).any(|r| r.body.iter().all(plus))
+                        && attacking(&instances, &negated).all(|s| {
+                            s.body.iter().any(minus)
+                                || supporting(&instances, q)
+                                    .any(|t| t.body.iter().all(plus) && superior(t, s))
+                        })));
+            let unprovable = !refuted.contains(q)
+                && !definite.contains(q)
+                && (definite.contains(&negated)
+                    || supporting(&instances, q).all(|r| r.body.iter().any(minus))
+                    || attacking(&instances, &negated).any(|s| {
+                        s.body.iter().all(plus)
+                            && supporting(&instances, q)
+                                .all(|t| t.body.iter().any(minus) || !superior(t, s))
+                    }));
+            if provable {
+                proved.insert(q.clone());
+                changed = true;
+            }
+            if unprovable {
+                refuted.insert(q.clone());
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    DefeasibleModel {
+        definite: literals
+            .iter()
+            .filter(|l| definite.contains(*l))
+            .cloned()
+            .collect(),
+        defeasible: literals
+            .iter()
+            .filter(|l| proved.contains(*l))
+            .cloned()
+            .collect(),
+        refuted,
+        literals: seen,
+    }
+}
+
+/// Strict and defeasible instances concluding `q`
+fn supporting<'a>(instances: &'a [Instance], q: &'a Term) -> impl Iterator<Item = &'a Instance> {
+    instances
+        .iter()
+        .filter(move |i| i.kind != RuleKind::Defeater && i.head == *q)
+}
+
+/// Instances of any kind concluding `negated`, the complement of a literal
+fn attacking<'a>(
+    instances: &'a [Instance],
+    negated: &'a Term,
+) -> impl Iterator<Item = &'a Instance> {
+    instances.iter().filter(move |i| i.head == *negated)
+}
+
+/// `neg(p)` for `p`, and `p` for `neg(p)`
+fn complement(literal: &Term) -> Term {
+    match literal {
+        Term::Compound(f, args) if f == "neg" && args.len() == 1 => args[0].clone(),
+        _ => Term::compound("neg", vec![literal.clone()]),
+    }
+}
+
+/// Ground instances of every rule whose conditions match known literals
+fn instantiate(
+    rules: &[Rule],
+    compiled: &[(Vec<Term>, Vec<Term>)],
+    known: &[Term],
+) -> Vec<Instance> {
+    let mut instances = Vec::new();
+    for (index, (conditions, conclusions)) in compiled.iter().enumerate() {
+        let mut partial = vec![Substitution::new()];
+        for condition in conditions.iter().filter(|c| !builtin::is_builtin(c)) {
+            partial = partial
+                .iter()
+                .flat_map(|bindings| {
+                    let pattern = bindings.apply(condition);
+                    known
+                        .iter()
+                        .filter_map(move |fact| unify(&pattern, fact, bindings))
+                })
+                .collect();
+        }
+        for bindings in partial {
+            let holds = conditions
+                .iter()
+                .filter(|c| builtin::is_builtin(c))
+                .all(|c| builtin::holds(c, &bindings));
+            if !holds {
+                continue;
+            }
+            let body: Vec<Term> = conditions
+                .iter()
+                .filter(|c| !builtin::is_builtin(c))
+                .map(|c| bindings.apply(c))
+                .collect();
+            for conclusion in conclusions {
+                let head = bindings.apply(conclusion);
+                if head.is_ground() {
+                    instances.push(Instance {
+                        rule: index,
+                        kind: rules[index].kind(),
+                        body: body.clone(),
+                        head,
+                    });
+                }
+            }
+        }
+    }
+    instances
+}
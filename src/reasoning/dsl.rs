@@ -0,0 +1,228 @@
+use super::term::{is_ident_char, TermParser};
+use super::{Rule, RuleKind};
+use crate::CognitionError;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Error in rule or term text, with the position where parsing stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    message: String,
+    line: usize,
+    column: usize,
+    file: Option<String>,
+}
+
+impl SyntaxError {
+    /// Error at a byte offset into `text`; lines and columns count from 1
+    pub(crate) fn at(text: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit_once('\n')
+            .map_or(before, |(_, last)| last)
+            .chars()
+            .count()
+            + 1;
+        Self {
+            message: message.into(),
+            line,
+            column,
+            file: None,
+        }
+    }
+
+    fn in_file(mut self, file: &Path) -> Self {
+        self.file = Some(file.display().to_string());
+        self
+    }
+
+    /// Get the message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the line (1-based)
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Get the column (1-based, in characters)
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Get the file the text was loaded from, if any
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(
+                f,
+                "{}:{}:{}: {}",
+                file, self.line, self.column, self.message
+            ),
+            None => write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Parse rules written in the rule language
+///
+/// ```text
+/// % Comments run to the end of the line (`//` works too)
+/// rule hot: temperature(X), X > 25 => hot.
+/// rule alarm salience 10: hot, humidity(H), H >= 80 => alarm(high).
+/// rule storm strength 0.7: pressure(low), wind(high) => storm.
+/// rule birds_fly defeasible: bird(X) => flies(X).
+/// rule penguins defeasible over birds_fly: penguin(X) => neg(flies(X)).
+/// rule injured defeater: injured(X) => neg(flies(X)).
+/// ```
+///
+/// Conditions are terms or comparisons (`<`, `<=`, `>`, `>=`, `==`, `!=`);
+/// conclusions are terms. Negation is rejected because forward and
+/// backward chaining do not evaluate it; `DatalogProgram` does. Rules are
+/// strict unless marked `defeasible` or `defeater`; `over NAME` makes a
+/// rule superior to another.
+pub fn parse_rules(source: &str) -> Result<Vec<Rule>, SyntaxError> {
+    let mut parser = TermParser::with_comments(source);
+    let mut rules = Vec::new();
+    let mut names = HashSet::new();
+    loop {
+        parser.skip_whitespace();
+        if parser.at_end() {
+            return Ok(rules);
+        }
+        if !parser.keyword("rule") {
+            return Err(parser.error("expected 'rule'"));
+        }
+        parser.skip_whitespace();
+        let start = parser.position();
+        let name = parser.take_while(is_ident_char);
+        if name.is_empty() {
+            return Err(parser.error("expected a rule name"));
+        }
+        if !names.insert(name) {
+            return Err(parser.error_at(start, &format!("duplicate rule '{}'", name)));
+        }
+        let mut rule = Rule::new(name);
+
+        loop {
+            if parser.keyword("salience") {
+                parser.skip_whitespace();
+                let start = parser.position();
+                let salience = parser
+                    .term()?
+                    .as_number()
+                    .filter(|n| n.fract() == 0.0)
+                    .ok_or_else(|| parser.error_at(start, "salience must be an integer"))?;
+                rule = rule.with_salience(salience as i32);
+            } else if parser.keyword("strength") {
+                parser.skip_whitespace();
+                let start = parser.position();
+                let strength = parser
+                    .term()?
+                    .as_number()
+                    .filter(|n| (0.0..=1.0).contains(n))
+                    .ok_or_else(|| {
+                        parser.error_at(start, "strength must be a number from 0 to 1")
+                    })?;
+                rule = rule.with_strength(strength);
+            } else if parser.keyword("strict") {
+                rule = rule.with_kind(RuleKind::Strict);
+            } else if parser.keyword("defeasible") {
+                rule = rule.with_kind(RuleKind::Defeasible);
+            } else if parser.keyword("defeater") {
+                rule = rule.with_kind(RuleKind::Defeater);
+            } else if parser.keyword("over") {
+                parser.skip_whitespace();
+                let weaker = parser.take_while(is_ident_char);
+                if weaker.is_empty() {
+                    return Err(parser.error("expected a rule name after 'over'"));
+                }
+                rule = rule.with_superiority_over(weaker);
+            } else {
+                break;
+            }
+        }
+        parser.expect(':')?;
+
+        parser.skip_whitespace();
+        if !parser.keyword_symbol("=>") {
+            loop {
+                parser.skip_whitespace();
+                let start = parser.position();
+                if parser.keyword("not") {
+                    return Err(parser.error_at(
+                        start,
+                        "negated conditions are not supported; use DatalogProgram for 'not'",
+                    ));
+                }
+                rule = rule.with_condition(parser.condition()?.to_string());
+                parser.skip_whitespace();
+                if parser.peek() == Some(',') {
+                    parser.bump();
+                } else if parser.keyword_symbol("=>") {
+                    break;
+                } else {
+                    return Err(parser.error("expected ',' or '=>'"));
+                }
+            }
+        }
+
+        loop {
+            rule = rule.with_conclusion(parser.term()?.to_string());
+            parser.skip_whitespace();
+            match parser.peek() {
+                Some(',') => {
+                    parser.bump();
+                }
+                Some('.') => {
+                    parser.bump();
+                    break;
+                }
+                _ => return Err(parser.error("expected ',' or '.'")),
+            }
+        }
+        rules.push(rule);
+    }
+}
+
+/// Read and parse rule files in order
+///
+/// Syntax errors name the file they occurred in; rule names must be unique
+/// across all files.
+pub fn load_rules<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<Vec<Rule>, CognitionError> {
+    let mut rules: Vec<Rule> = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            CognitionError::ReasoningError(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        let parsed = parse_rules(&source).map_err(|e| e.in_file(path))?;
+        for rule in parsed {
+            if rules.iter().any(|r| r.name() == rule.name()) {
+                return Err(CognitionError::ReasoningError(format!(
+                    "duplicate rule '{}' in {}",
+                    rule.name(),
+                    path.display()
+                )));
+            }
+            rules.push(rule);
+        }
+    }
+    Ok(rules)
+}
@@ -1,32 +1,704 @@
-use super::Rule;
+use super::agenda;
+use super::builtin;
+use super::defeasible;
+use super::lint;
+use super::matcher::{Activation, FactId, IncrementalMatcher};
+use super::tms::TruthMaintenance;
+use super::{
+    lint_rules, parse_rules, unify, CertaintyCombination, ConflictStrategy, DefeasibleModel,
+    Diagnostic, Justification, MatchStrategy, Ontology, ProofStep, ProofTree, QueryOptions, Rule,
+    RuleKind, Solutions, Substitution, Term,
+};
+use crate::bdi::{Belief, BeliefBase, Source, Value};
+use crate::explanation::Explanation;
+use crate::CognitionError;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-/// Simple reasoning engine
-#[derive(Debug)]
+/// Rule with its conditions and conclusions parsed into terms
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledRule {
+    pub(crate) name: String,
+    pub(crate) conditions: Vec<Term>,
+    pub(crate) conclusions: Vec<Term>,
+    pub(crate) salience: i32,
+    pub(crate) strength: f64,
+}
+
+impl CompiledRule {
+    fn new(rule: &Rule) -> Self {
+        Self {
+            name: rule.name().to_string(),
+            salience: rule.salience(),
+            strength: rule.strength(),
+            conditions: rule.condition_terms(),
+            conclusions: rule.conclusion_terms(),
+        }
+    }
+}
+
+/// Forward-chaining reasoning engine
+#[derive(Debug, Clone)]
 pub struct ReasoningEngine {
     rules: Vec<Rule>,
+    compiled: Vec<CompiledRule>,
+    /// Working memory keyed by id; ids grow with every assertion
+    facts: HashMap<FactId, Term>,
+    ids: HashMap<Term, FactId>,
+    index: HashMap<(String, usize), BTreeSet<FactId>>,
+    next_id: FactId,
+    strategy: MatchStrategy,
+    matcher: IncrementalMatcher,
+    conflict: ConflictStrategy,
+    /// Instantiations fired by the naive matcher (refraction)
+    fired: HashSet<(usize, Vec<FactId>)>,
+    tms: TruthMaintenance,
+    /// Premises asserted by `sync_beliefs`
+    belief_facts: HashSet<Term>,
+    /// Premises asserted by `sync_ontology`
+    ontology_facts: HashSet<Term>,
+    combination: CertaintyCombination,
+    threshold: f64,
 }
 
 impl ReasoningEngine {
     /// Create a new reasoning engine
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            compiled: Vec::new(),
+            facts: HashMap::new(),
+            ids: HashMap::new(),
+            index: HashMap::new(),
+            next_id: 0,
+            strategy: MatchStrategy::default(),
+            matcher: IncrementalMatcher::default(),
+            conflict: ConflictStrategy::default(),
+            fired: HashSet::new(),
+            tms: TruthMaintenance::default(),
+            belief_facts: HashSet::new(),
+            ontology_facts: HashSet::new(),
+            combination: CertaintyCombination::default(),
+            threshold: 0.0,
+        }
+    }
+
+    /// Create an engine with the given matching strategy
+    pub fn with_matching(strategy: MatchStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::new()
+        }
+    }
+
+    /// Get the matching strategy
+    pub fn matching(&self) -> MatchStrategy {
+        self.strategy
+    }
+
+    /// Set the conflict resolution strategy
+    pub fn set_conflict_strategy(&mut self, conflict: ConflictStrategy) {
+        self.conflict = conflict;
+    }
+
+    /// Get the conflict resolution strategy
+    pub fn conflict_strategy(&self) -> ConflictStrategy {
+        self.conflict
+    }
+
+    /// Set how the certainties of a rule's conditions combine
+    pub fn set_certainty_combination(&mut self, combination: CertaintyCombination) {
+        self.combination = combination;
+    }
+
+    /// Get the certainty combination
+    pub fn certainty_combination(&self) -> CertaintyCombination {
+        self.combination
+    }
+
+    /// Set the minimum certainty a conclusion needs to be asserted
+    pub fn set_certainty_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Get the certainty threshold
+    pub fn certainty_threshold(&self) -> f64 {
+        self.threshold
     }
 
     /// Add a rule
+    ///
+    /// Only strict rules take part in forward and backward chaining;
+    /// defeasible rules and defeaters are used by `defeasible_model`.
+    /// Conditions negated with `not` are only evaluated by `DatalogProgram`;
+    /// `lint` reports them as errors.
     pub fn add_rule(&mut self, rule: Rule) {
+        if rule.kind() == RuleKind::Strict {
+            let compiled = CompiledRule::new(&rule);
+            if self.strategy == MatchStrategy::Incremental {
+                self.matcher.add_rule(&compiled, ordered(&self.facts));
+            }
+            self.compiled.push(compiled);
+        }
         self.rules.push(rule);
     }
 
+    /// Add every rule in rule-language text; returns how many were added
+    pub fn add_rules_from(&mut self, source: &str) -> Result<usize, CognitionError> {
+        let rules = parse_rules(source)?;
+        let count = rules.len();
+        rules.into_iter().for_each(|rule| self.add_rule(rule));
+        Ok(count)
+    }
+
     /// Get all rules
     pub fn rules(&self) -> &[Rule] {
         &self.rules
     }
 
-    /// Perform inference (placeholder)
-    pub fn infer(&self) -> Vec<String> {
-        // Placeholder - real implementation would perform forward/backward chaining
-        Vec::new()
+    /// Add a fact such as `human(socrates)`; returns false if already known
+    ///
+    /// Text that is not a valid term is stored as an opaque atom. Facts must
+    /// be ground; facts containing variables are rejected.
+    pub fn add_fact(&mut self, fact: impl Into<String>) -> bool {
+        self.add_term(Term::parse_lenient(&fact.into()))
+    }
+
+    /// Add a ground term as a fact; returns false if already known or not ground
+    ///
+    /// The fact becomes a premise. Derived facts it supported before being
+    /// retracted are restored.
+    pub fn add_term(&mut self, fact: Term) -> bool {
+        self.add_term_with_certainty(fact, 1.0)
+    }
+
+    /// Add a fact with a certainty between 0.0 and 1.0
+    ///
+    /// Asserting a known premise again updates its certainty.
+    pub fn add_fact_with_certainty(&mut self, fact: impl Into<String>, certainty: f64) -> bool {
+        self.add_term_with_certainty(Term::parse_lenient(&fact.into()), certainty)
+    }
+
+    fn add_term_with_certainty(&mut self, fact: Term, certainty: f64) -> bool {
+        if !fact.is_ground() {
+            return false;
+        }
+        self.tms
+            .add_premise(fact.clone(), certainty.clamp(0.0, 1.0));
+        if self.ids.contains_key(&fact) {
+            // Conclusions may now fall below the threshold or newly qualify
+            self.retract_below_threshold(&fact);
+            self.restore_from(&fact);
+            return false;
+        }
+        self.assert_term(fact)
+    }
+
+    /// Retract conclusions depending on `fact` that fall below the certainty
+    /// threshold, and those left without support as a result
+    fn retract_below_threshold(&mut self, fact: &Term) {
+        let dependents = self.tms.dependents(fact);
+        loop {
+            let believed = |t: &Term| self.ids.contains_key(t);
+            let stale: Vec<Term> = dependents
+                .iter()
+                .filter(|d| believed(d) && !self.tms.is_premise(d))
+                .filter(|d| {
+                    self.tms.valid(d, believed).next().is_none()
+                        || self.certainty_of(d) < self.threshold
+                })
+                .cloned()
+                .collect();
+            if stale.is_empty() {
+                break;
+            }
+            for conclusion in &stale {
+                self.remove(conclusion);
+            }
+        }
+    }
+
+    /// Certainty of a fact; 0.0 if it is not believed
+    ///
+    /// Derived certainties follow the current support, so they change as
+    /// facts are added and retracted.
+    pub fn certainty(&self, fact: &str) -> f64 {
+        let fact = Term::parse_lenient(fact);
+        if !self.ids.contains_key(&fact) {
+            return 0.0;
+        }
+        self.certainty_of(&fact)
+    }
+
+    fn certainty_of(&self, fact: &Term) -> f64 {
+        let believed = |t: &Term| self.ids.contains_key(t);
+        self.tms.certainty(fact, self.combination, &believed)
+    }
+
+    /// Put a fact in working memory and restore conclusions it supports
+    fn assert_term(&mut self, fact: Term) -> bool {
+        if self.ids.contains_key(&fact) {
+            return false;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some((name, arity)) = fact.functor() {
+            self.index
+                .entry((name.to_string(), arity))
+                .or_default()
+                .insert(id);
+        }
+        if self.strategy == MatchStrategy::Incremental {
+            self.matcher.assert(&self.compiled, id, &fact);
+        }
+        self.ids.insert(fact.clone(), id);
+        self.facts.insert(id, fact.clone());
+        self.restore_from(&fact);
+        true
+    }
+
+    /// Assert conclusions whose recorded support `fact` completes
+    fn restore_from(&mut self, fact: &Term) {
+        for conclusion in self.tms.restorable(fact, |t| self.ids.contains_key(t)) {
+            if self.certainty_of(&conclusion) >= self.threshold {
+                self.assert_term(conclusion);
+            }
+        }
+    }
+
+    /// Withdraw a fact as a premise; returns false if it was not known
+    ///
+    /// The fact stays believed while a rule still derives it from other
+    /// facts. Derived facts left without support are retracted too, and come
+    /// back when their support is asserted again.
+    pub fn retract_fact(&mut self, fact: &str) -> bool {
+        self.retract_term(&Term::parse_lenient(fact))
+    }
+
+    /// Withdraw a fact given as a term; returns false if it was not known
+    pub fn retract_term(&mut self, fact: &Term) -> bool {
+        let known = self.ids.contains_key(fact);
+        self.retract_with_dependents(fact);
+        known
+    }
+
+    /// Withdraw a premise and remove every fact left unsupported; returns them
+    fn retract_with_dependents(&mut self, fact: &Term) -> Vec<Term> {
+        self.tms.remove_premise(fact);
+        if !self.ids.contains_key(fact) {
+            return Vec::new();
+        }
+        let unsupported = self.tms.unsupported(fact, |t| self.ids.contains_key(t));
+        for stale in &unsupported {
+            self.remove(stale);
+        }
+        unsupported
+    }
+
+    /// Take a fact out of working memory and the matcher
+    fn remove(&mut self, fact: &Term) -> bool {
+        let Some(id) = self.ids.remove(fact) else {
+            return false;
+        };
+        self.facts.remove(&id);
+        if let Some((name, arity)) = fact.functor() {
+            if let Some(ids) = self.index.get_mut(&(name.to_string(), arity)) {
+                ids.remove(&id);
+            }
+        }
+        if self.strategy == MatchStrategy::Incremental {
+            self.matcher.retract(id);
+        }
+        self.fired.retain(|(_, facts)| !facts.contains(&id));
+        true
+    }
+
+    /// Check if a fact is known
+    pub fn has_fact(&self, fact: &str) -> bool {
+        self.ids.contains_key(&Term::parse_lenient(fact))
+    }
+
+    /// Get all facts in the order they became known
+    pub fn facts(&self) -> Vec<&Term> {
+        self.ordered_facts()
+            .into_iter()
+            .map(|(_, fact)| fact)
+            .collect()
+    }
+
+    /// Facts with their ids, in assertion order
+    fn ordered_facts(&self) -> Vec<(FactId, &Term)> {
+        ordered(&self.facts)
     }
+
+    /// Facts in assertion order, as owned terms
+    fn fact_terms(&self) -> Vec<Term> {
+        self.facts().into_iter().cloned().collect()
+    }
+
+    /// Forget all facts (rules are kept)
+    pub fn clear_facts(&mut self) {
+        self.facts.clear();
+        self.ids.clear();
+        self.index.clear();
+        self.matcher.clear_facts();
+        self.fired.clear();
+        self.tms.clear();
+        self.belief_facts.clear();
+        self.ontology_facts.clear();
+    }
+
+    /// Explain why a fact is believed
+    ///
+    /// Premises are leaves; derived facts show the rule and the facts it
+    /// matched. Returns `None` if the fact is not believed.
+    pub fn why(&self, fact: &str) -> Option<ProofTree> {
+        self.proof(&Term::parse_lenient(fact), &mut Vec::new())
+    }
+
+    /// Explain a believed fact through the rule firings and supporting
+    /// facts that derived it
+    pub fn explain(&self, fact: &str) -> Option<Explanation> {
+        self.why(fact).as_ref().map(Explanation::from)
+    }
+
+    fn proof(&self, fact: &Term, visiting: &mut Vec<Term>) -> Option<ProofTree> {
+        if !self.ids.contains_key(fact) || visiting.contains(fact) {
+            return None;
+        }
+        if self.tms.is_premise(fact) {
+            return Some(ProofTree::new(fact.clone(), ProofStep::Fact, Vec::new()));
+        }
+        visiting.push(fact.clone());
+        let tree = self
+            .tms
+            .valid(fact, |t| self.ids.contains_key(t))
+            .find_map(|justification| {
+                let children = justification
+                    .antecedents()
+                    .iter()
+                    .map(|a| self.proof(a, visiting))
+                    .collect::<Option<Vec<_>>>()?;
+                let step = ProofStep::Rule(justification.rule().to_string());
+                Some(ProofTree::new(fact.clone(), step, children))
+            });
+        visiting.pop();
+        tree
+    }
+
+    /// Current justifications of a derived fact
+    pub fn justifications(&self, fact: &str) -> Vec<&Justification> {
+        self.tms
+            .valid(&Term::parse_lenient(fact), |t| self.ids.contains_key(t))
+            .collect()
+    }
+
+    /// Mirror a belief base as premises `key(value)` with the beliefs'
+    /// certainties
+    ///
+    /// Beliefs no longer in the base are retracted along with the
+    /// conclusions that depended on them; returns every retracted fact.
+    pub fn sync_beliefs(&mut self, beliefs: &BeliefBase) -> Vec<String> {
+        let current: HashMap<Term, f64> = beliefs
+            .all()
+            .map(|b| (Term::from(b), b.certainty()))
+            .collect();
+        let stale: Vec<Term> = self
+            .belief_facts
+            .iter()
+            .filter(|fact| !current.contains_key(fact))
+            .cloned()
+            .collect();
+        let mut retracted = Vec::new();
+        for fact in stale {
+            self.belief_facts.remove(&fact);
+            retracted.extend(self.retract_with_dependents(&fact));
+        }
+        for (fact, certainty) in current {
+            self.belief_facts.insert(fact.clone());
+            self.add_term_with_certainty(fact, certainty);
+        }
+        retracted.iter().map(Term::to_string).collect()
+    }
+
+    /// Derived facts as beliefs carrying their computed certainty
+    ///
+    /// `key(value)` becomes a belief `key = value`, an atom `key` becomes
+    /// `key = true`, and any other fact is keyed by its text. Numbers and
+    /// `true`/`false` become typed values, other atoms symbols. The rule
+    /// that derived a fact is its source and the facts it matched are its
+    /// evidence.
+    pub fn derived_beliefs(&self) -> Vec<Belief> {
+        self.facts()
+            .into_iter()
+            .filter(|fact| !self.tms.is_premise(fact))
+            .map(|fact| {
+                let (key, value) = match fact {
+                    Term::Atom(name) => (name.clone(), Value::Bool(true)),
+                    Term::Compound(name, args) if args.len() == 1 => {
+                        (name.clone(), Value::from(&args[0]))
+                    }
+                    _ => (fact.to_string(), Value::Bool(true)),
+                };
+                let belief = Belief::with_certainty(key, value, self.certainty_of(fact));
+                match self.tms.valid(fact, |t| self.ids.contains_key(t)).next() {
+                    Some(justification) => justification.antecedents().iter().fold(
+                        belief.with_source(Source::Inference(justification.rule().to_string())),
+                        |belief, antecedent| belief.with_evidence(antecedent.to_string()),
+                    ),
+                    None => belief,
+                }
+            })
+            .collect()
+    }
+
+    /// Bindings for every fact that unifies with a pattern
+    pub fn facts_matching(&self, pattern: &Term) -> Vec<Substitution> {
+        self.candidates(pattern)
+            .filter_map(|fact| unify(pattern, fact, &Substitution::new()))
+            .collect()
+    }
+
+    /// Check the rules for likely mistakes given the current facts
+    ///
+    /// Besides the checks of `lint_rules`, conditions negated with `not` are
+    /// reported as errors, since the engine never evaluates them.
+    pub fn lint(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = lint::negated_conditions(&self.rules);
+        diagnostics.extend(lint_rules(&self.rules, &self.fact_terms()));
+        diagnostics
+    }
+
+    /// Add an ontology's closure as facts; returns how many were new
+    ///
+    /// Rule conditions can then match `subclass_of(C, A)`,
+    /// `instance_of(I, C)`, `property(E, Name, Value)` and `disjoint(A, B)`.
+    /// The facts are a snapshot: later changes to the ontology are not seen
+    /// until it is added again, and nothing is ever retracted. Use
+    /// `sync_ontology` to follow an ontology that changes.
+    pub fn add_ontology(&mut self, ontology: &Ontology) -> usize {
+        ontology
+            .to_terms()
+            .into_iter()
+            .filter(|term| self.add_term(term.clone()))
+            .count()
+    }
+
+    /// Mirror an ontology's closure as facts
+    ///
+    /// Closure facts from an earlier sync that no longer follow are
+    /// retracted along with the conclusions that depended on them; returns
+    /// every retracted fact.
+    pub fn sync_ontology(&mut self, ontology: &Ontology) -> Vec<String> {
+        let terms = ontology.to_terms();
+        let current: HashSet<&Term> = terms.iter().collect();
+        let stale: Vec<Term> = self
+            .ontology_facts
+            .iter()
+            .filter(|fact| !current.contains(fact))
+            .cloned()
+            .collect();
+        let mut retracted = Vec::new();
+        for fact in stale {
+            self.ontology_facts.remove(&fact);
+            retracted.extend(self.retract_with_dependents(&fact));
+        }
+        for fact in terms {
+            self.ontology_facts.insert(fact.clone());
+            self.add_term(fact);
+        }
+        retracted.iter().map(Term::to_string).collect()
+    }
+
+    /// Conclusions of the facts and every rule under defeasible logic
+    ///
+    /// Strict rules give definite conclusions; defeasible rules give
+    /// conclusions that hold unless a rule for the complement `neg(p)` is
+    /// applicable and not beaten by a superior rule (see
+    /// `Rule::with_superiority_over`).
+    pub fn defeasible_model(&self) -> DefeasibleModel {
+        defeasible::evaluate(&self.fact_terms(), &self.rules)
+    }
+
+    /// Fire rules until no new facts can be derived
+    ///
+    /// Variables bound by the conditions are substituted into the
+    /// conclusions, so one rule applies to every matching combination of
+    /// facts. Eligible instantiations fire one at a time in the order chosen
+    /// by the conflict strategy, and each fires at most once. Returns the
+    /// newly derived facts in derivation order; cyclic rule sets terminate.
+    pub fn infer(&mut self) -> Vec<String> {
+        let mut derived = Vec::new();
+        while let Some(new_facts) = self.fire_next() {
+            derived.extend(new_facts);
+        }
+        derived
+    }
+
+    /// Fire the highest-priority eligible rule instantiation
+    ///
+    /// Returns the facts it derived, or `None` when nothing is eligible.
+    pub fn fire_next(&mut self) -> Option<Vec<String>> {
+        let activation = match self.strategy {
+            MatchStrategy::Incremental => {
+                let index = agenda::select(self.matcher.pending(), &self.compiled, self.conflict)?;
+                self.matcher.take(index)
+            }
+            MatchStrategy::Naive => {
+                let mut conflict_set = self.naive_conflict_set();
+                let index = agenda::select(&conflict_set, &self.compiled, self.conflict)?;
+                let activation = conflict_set.swap_remove(index);
+                self.fired
+                    .insert((activation.rule, activation.facts.clone()));
+                activation
+            }
+        };
+
+        let rule = &self.compiled[activation.rule];
+        let (name, strength) = (rule.name.clone(), rule.strength);
+        let conclusions = rule.conclusions.clone();
+        let antecedents: Vec<Term> = activation
+            .facts
+            .iter()
+            .filter_map(|&id| self.fact(id).cloned())
+            .collect();
+        let mut derived = Vec::new();
+        for conclusion in &conclusions {
+            let fact = activation.bindings.apply(conclusion);
+            self.tms
+                .justify(&fact, &name, strength, antecedents.clone());
+            if self.certainty_of(&fact) < self.threshold {
+                continue;
+            }
+            let text = fact.to_string();
+            if self.assert_term(fact) {
+                derived.push(text);
+            }
+        }
+        Some(derived)
+    }
+
+    /// Names of the rules with eligible instantiations, in firing order
+    pub fn agenda(&self) -> Vec<&str> {
+        let mut activations = match self.strategy {
+            MatchStrategy::Incremental => self.matcher.pending().to_vec(),
+            MatchStrategy::Naive => self.naive_conflict_set(),
+        };
+        let mut order = Vec::new();
+        while let Some(index) = agenda::select(&activations, &self.compiled, self.conflict) {
+            order.push(self.compiled[activations.remove(index).rule].name.as_str());
+        }
+        order
+    }
+
+    /// Every unfired instantiation, recomputed from scratch
+    fn naive_conflict_set(&self) -> Vec<Activation> {
+        let mut activations = Vec::new();
+        for (rule, compiled) in self.compiled.iter().enumerate() {
+            for (facts, bindings) in self.match_conditions(&compiled.conditions) {
+                if !self.fired.contains(&(rule, facts.clone())) {
+                    activations.push(Activation {
+                        rule,
+                        facts,
+                        bindings,
+                        sequence: activations.len() as u64,
+                    });
+                }
+            }
+        }
+        activations
+    }
+
+    /// Fact with the given id, if still in working memory
+    fn fact(&self, id: FactId) -> Option<&Term> {
+        self.facts.get(&id)
+    }
+
+    /// Prove a goal such as `mortal(X)` by backward chaining
+    ///
+    /// Solutions are produced lazily, each with the bindings of the goal's
+    /// variables and a proof tree.
+    pub fn query(&self, goal: &str) -> Result<Solutions<'_>, CognitionError> {
+        self.query_with(goal, QueryOptions::default())
+    }
+
+    /// Prove a goal with a depth limit and/or tabling
+    pub fn query_with(
+        &self,
+        goal: &str,
+        options: QueryOptions,
+    ) -> Result<Solutions<'_>, CognitionError> {
+        Ok(Solutions::new(self, Term::parse(goal)?, options))
+    }
+
+    /// All matches of the conditions against the known facts
+    fn match_conditions(&self, conditions: &[Term]) -> Vec<(Vec<FactId>, Substitution)> {
+        let mut partial = vec![(Vec::new(), Substitution::new())];
+        for condition in conditions.iter().filter(|c| !builtin::is_builtin(c)) {
+            let mut next = Vec::new();
+            for (facts, bindings) in &partial {
+                let pattern = bindings.apply(condition);
+                for id in self.candidate_ids(&pattern) {
+                    if let Some(extended) = unify(&pattern, &self.facts[&id], bindings) {
+                        let mut facts = facts.clone();
+                        facts.push(id);
+                        next.push((facts, extended));
+                    }
+                }
+            }
+            if next.is_empty() {
+                return next;
+            }
+            partial = next;
+        }
+        // Comparisons are tested once the facts have bound their variables
+        partial.retain(|(_, bindings)| {
+            conditions
+                .iter()
+                .filter(|c| builtin::is_builtin(c))
+                .all(|c| builtin::holds(c, bindings))
+        });
+        partial
+    }
+
+    /// Parsed rules, in the order they were added
+    pub(crate) fn compiled_rules(&self) -> &[CompiledRule] {
+        &self.compiled
+    }
+
+    /// Asserted facts, in assertion order, excluding derived ones
+    pub(crate) fn premises(&self) -> impl Iterator<Item = &Term> + '_ {
+        self.facts()
+            .into_iter()
+            .filter(move |f| self.tms.is_premise(f))
+    }
+
+    /// Facts that could unify with a pattern, using the functor index
+    pub(crate) fn candidates<'a>(&'a self, pattern: &Term) -> impl Iterator<Item = &'a Term> + 'a {
+        self.candidate_ids(pattern)
+            .into_iter()
+            .map(move |id| &self.facts[&id])
+    }
+
+    /// Ids of the facts that could unify with a pattern, in assertion order
+    fn candidate_ids(&self, pattern: &Term) -> Vec<FactId> {
+        match pattern.functor() {
+            Some((name, arity)) => self
+                .index
+                .get(&(name.to_string(), arity))
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default(),
+            None => ordered(&self.facts).into_iter().map(|(id, _)| id).collect(),
+        }
+    }
+}
+
+/// Facts with their ids, in assertion order
+///
+/// A free function so callers can borrow the facts alongside the matcher.
+fn ordered(facts: &HashMap<FactId, Term>) -> Vec<(FactId, &Term)> {
+    let mut facts: Vec<(FactId, &Term)> = facts.iter().map(|(&id, f)| (id, f)).collect();
+    facts.sort_unstable_by_key(|(id, _)| *id);
+    facts
 }
 
 impl Default for ReasoningEngine {
@@ -0,0 +1,494 @@
+use crate::bdi::BeliefBase;
+use crate::CognitionError;
+use std::collections::HashMap;
+
+/// Membership function of a fuzzy set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Membership {
+    /// Rises from `a` to a peak at `b`, falls to `c`
+    Triangular { a: f64, b: f64, c: f64 },
+    /// Rises from `a` to `b`, flat until `c`, falls to `d`
+    Trapezoidal { a: f64, b: f64, c: f64, d: f64 },
+    /// Bell curve around `mean`
+    Gaussian { mean: f64, sigma: f64 },
+}
+
+impl Membership {
+    /// Create a triangular membership function
+    pub fn triangular(a: f64, b: f64, c: f64) -> Self {
+        Membership::Triangular { a, b, c }
+    }
+
+    /// Create a trapezoidal membership function
+    pub fn trapezoidal(a: f64, b: f64, c: f64, d: f64) -> Self {
+        Membership::Trapezoidal { a, b, c, d }
+    }
+
+    /// Create a Gaussian membership function
+    pub fn gaussian(mean: f64, sigma: f64) -> Self {
+        Membership::Gaussian { mean, sigma }
+    }
+
+    /// Degree of membership of `x`, from 0.0 to 1.0
+    pub fn degree(&self, x: f64) -> f64 {
+        match *self {
+            Membership::Triangular { a, b, c } => Self::trapezoid(x, a, b, b, c),
+            Membership::Trapezoidal { a, b, c, d } => Self::trapezoid(x, a, b, c, d),
+            Membership::Gaussian { mean, sigma } => {
+                if sigma == 0.0 {
+                    return if x == mean { 1.0 } else { 0.0 };
+                }
+                (-((x - mean) / sigma).powi(2) / 2.0).exp()
+            }
+        }
+    }
+
+    /// Shoulders (`a == b` or `c == d`) hold full membership to the edge
+    fn trapezoid(x: f64, a: f64, b: f64, c: f64, d: f64) -> f64 {
+        if x < a || x > d {
+            0.0
+        } else if x < b {
+            (x - a) / (b - a)
+        } else if x <= c {
+            1.0
+        } else {
+            (d - x) / (d - c)
+        }
+    }
+}
+
+/// Named variable whose values are described by fuzzy terms
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinguisticVariable {
+    name: String,
+    min: f64,
+    max: f64,
+    terms: Vec<(String, Membership)>,
+}
+
+impl LinguisticVariable {
+    /// Create a variable over the range `min..=max`
+    pub fn new(name: impl Into<String>, min: f64, max: f64) -> Self {
+        Self {
+            name: name.into(),
+            min,
+            max,
+            terms: Vec::new(),
+        }
+    }
+
+    /// Add a term such as `high`
+    pub fn with_term(mut self, term: impl Into<String>, membership: Membership) -> Self {
+        self.terms.push((term.into(), membership));
+        self
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the range
+    pub fn range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    /// Get the membership function of a term
+    pub fn term(&self, term: &str) -> Option<&Membership> {
+        self.terms.iter().find(|(t, _)| t == term).map(|(_, m)| m)
+    }
+
+    /// Degree of every term for a crisp value
+    pub fn fuzzify(&self, x: f64) -> Vec<(&str, f64)> {
+        self.terms
+            .iter()
+            .map(|(term, membership)| (term.as_str(), membership.degree(x)))
+            .collect()
+    }
+}
+
+/// How a Mamdani output set is turned back into a crisp value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Defuzzification {
+    /// Center of gravity of the output set
+    #[default]
+    Centroid,
+    /// Value splitting the area under the output set in half
+    Bisector,
+    /// Mean of the values with maximal membership
+    MeanOfMaxima,
+}
+
+/// What a fuzzy rule concludes
+#[derive(Debug, Clone, PartialEq)]
+pub enum FuzzyConsequent {
+    /// Mamdani: the output variable takes a fuzzy term
+    Term { variable: String, term: String },
+    /// Sugeno: the output is `constant + Σ coefficient × input`
+    Function {
+        variable: String,
+        constant: f64,
+        coefficients: Vec<(String, f64)>,
+    },
+}
+
+impl FuzzyConsequent {
+    fn variable(&self) -> &str {
+        match self {
+            FuzzyConsequent::Term { variable, .. } | FuzzyConsequent::Function { variable, .. } => {
+                variable
+            }
+        }
+    }
+}
+
+/// Rule such as "if temperature is high and humidity is high then fan is fast"
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyRule {
+    name: String,
+    conditions: Vec<(String, String)>,
+    consequents: Vec<FuzzyConsequent>,
+    weight: f64,
+}
+
+impl FuzzyRule {
+    /// Create a new rule
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            conditions: Vec::new(),
+            consequents: Vec::new(),
+            weight: 1.0,
+        }
+    }
+
+    /// Add a condition "`variable` is `term`"; conditions are combined with min
+    pub fn with_condition(mut self, variable: impl Into<String>, term: impl Into<String>) -> Self {
+        self.conditions.push((variable.into(), term.into()));
+        self
+    }
+
+    /// Add a Mamdani conclusion "`variable` is `term`"
+    pub fn with_conclusion(mut self, variable: impl Into<String>, term: impl Into<String>) -> Self {
+        self.consequents.push(FuzzyConsequent::Term {
+            variable: variable.into(),
+            term: term.into(),
+        });
+        self
+    }
+
+    /// Add a Sugeno conclusion `variable = constant + Σ coefficient × input`
+    pub fn with_function(
+        mut self,
+        variable: impl Into<String>,
+        constant: f64,
+        coefficients: &[(&str, f64)],
+    ) -> Self {
+        self.consequents.push(FuzzyConsequent::Function {
+            variable: variable.into(),
+            constant,
+            coefficients: coefficients
+                .iter()
+                .map(|(input, c)| (input.to_string(), *c))
+                .collect(),
+        });
+        self
+    }
+
+    /// Set the weight the firing strength is scaled by (clamped to 0.0..=1.0)
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get conditions as (variable, term) pairs
+    pub fn conditions(&self) -> &[(String, String)] {
+        &self.conditions
+    }
+
+    /// Get consequents
+    pub fn consequents(&self) -> &[FuzzyConsequent] {
+        &self.consequents
+    }
+}
+
+/// Fuzzy inference system with Mamdani and/or Sugeno rules
+///
+/// Each output is either driven by Mamdani rules (clipped output sets
+/// aggregated with max, then defuzzified) or by Sugeno rules (firing-strength
+/// weighted average of the rule functions), not both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzySystem {
+    inputs: Vec<LinguisticVariable>,
+    outputs: Vec<LinguisticVariable>,
+    rules: Vec<FuzzyRule>,
+    defuzzification: Defuzzification,
+    resolution: usize,
+}
+
+impl FuzzySystem {
+    /// Create an empty system
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            rules: Vec::new(),
+            defuzzification: Defuzzification::default(),
+            resolution: 201,
+        }
+    }
+
+    /// Add an input variable
+    pub fn with_input(mut self, variable: LinguisticVariable) -> Self {
+        self.inputs.push(variable);
+        self
+    }
+
+    /// Add an output variable
+    pub fn with_output(mut self, variable: LinguisticVariable) -> Self {
+        self.outputs.push(variable);
+        self
+    }
+
+    /// Add a rule
+    pub fn with_rule(mut self, rule: FuzzyRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Set the defuzzification method for Mamdani outputs
+    pub fn with_defuzzification(mut self, method: Defuzzification) -> Self {
+        self.defuzzification = method;
+        self
+    }
+
+    /// Set how many points output sets are sampled at (at least 2)
+    pub fn with_resolution(mut self, points: usize) -> Self {
+        self.resolution = points.max(2);
+        self
+    }
+
+    /// Get the rules
+    pub fn rules(&self) -> &[FuzzyRule] {
+        &self.rules
+    }
+
+    /// Compute crisp outputs from crisp inputs
+    pub fn evaluate(
+        &self,
+        inputs: &HashMap<String, f64>,
+    ) -> Result<HashMap<String, f64>, CognitionError> {
+        let strengths = self
+            .rules
+            .iter()
+            .map(|rule| self.firing_strength(rule, inputs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut results = HashMap::new();
+        let outputs = self
+            .rules
+            .iter()
+            .flat_map(|r| &r.consequents)
+            .map(FuzzyConsequent::variable);
+        for output in outputs {
+            if results.contains_key(output) {
+                continue;
+            }
+            let consequents: Vec<(f64, &FuzzyConsequent)> = self
+                .rules
+                .iter()
+                .zip(&strengths)
+                .flat_map(|(rule, &strength)| {
+                    rule.consequents
+                        .iter()
+                        .filter(|c| c.variable() == output)
+                        .map(move |c| (strength, c))
+                })
+                .collect();
+            let value = if consequents
+                .iter()
+                .all(|(_, c)| matches!(c, FuzzyConsequent::Term { .. }))
+            {
+                self.mamdani(output, &consequents)?
+            } else if consequents
+                .iter()
+                .all(|(_, c)| matches!(c, FuzzyConsequent::Function { .. }))
+            {
+                Self::sugeno(output, &consequents, inputs)?
+            } else {
+                return Err(CognitionError::ReasoningError(format!(
+                    "output '{}' mixes Mamdani and Sugeno rules",
+                    output
+                )));
+            };
+            results.insert(output.to_string(), value);
+        }
+        Ok(results)
+    }
+
+    /// Compute outputs reading each input from the belief with the same key
+    pub fn evaluate_beliefs(
+        &self,
+        beliefs: &BeliefBase,
+    ) -> Result<HashMap<String, f64>, CognitionError> {
+        let mut inputs = HashMap::new();
+        for variable in &self.inputs {
+            let belief = beliefs.get(variable.name()).ok_or_else(|| {
+                CognitionError::ReasoningError(format!("no belief for input '{}'", variable.name()))
+            })?;
+            let value = belief.as_f64().ok_or_else(|| {
+                CognitionError::ReasoningError(format!(
+                    "belief '{}' is not numeric: '{}'",
+                    variable.name(),
+                    belief.value()
+                ))
+            })?;
+            inputs.insert(variable.name().to_string(), value);
+        }
+        self.evaluate(&inputs)
+    }
+
+    fn firing_strength(
+        &self,
+        rule: &FuzzyRule,
+        inputs: &HashMap<String, f64>,
+    ) -> Result<f64, CognitionError> {
+        let mut strength: f64 = 1.0;
+        for (name, term) in &rule.conditions {
+            let variable = self.input(name)?;
+            let membership = variable.term(term).ok_or_else(|| {
+                CognitionError::ReasoningError(format!("'{}' has no term '{}'", name, term))
+            })?;
+            let x = *inputs.get(name).ok_or_else(|| {
+                CognitionError::ReasoningError(format!("missing input '{}'", name))
+            })?;
+            strength = strength.min(membership.degree(x));
+        }
+        Ok(strength * rule.weight)
+    }
+
+    fn input(&self, name: &str) -> Result<&LinguisticVariable, CognitionError> {
+        self.inputs
+            .iter()
+            .find(|v| v.name == name)
+            .ok_or_else(|| CognitionError::ReasoningError(format!("unknown input '{}'", name)))
+    }
+
+    fn mamdani(
+        &self,
+        output: &str,
+        consequents: &[(f64, &FuzzyConsequent)],
+    ) -> Result<f64, CognitionError> {
+        let variable = self
+            .outputs
+            .iter()
+            .find(|v| v.name == output)
+            .ok_or_else(|| {
+                CognitionError::ReasoningError(format!("unknown output '{}'", output))
+            })?;
+        let mut clipped = Vec::new();
+        for (strength, consequent) in consequents {
+            if let FuzzyConsequent::Term { term, .. } = consequent {
+                let membership = variable.term(term).ok_or_else(|| {
+                    CognitionError::ReasoningError(format!("'{}' has no term '{}'", output, term))
+                })?;
+                clipped.push((*strength, membership));
+            }
+        }
+
+        let step = (variable.max - variable.min) / (self.resolution - 1) as f64;
+        let xs: Vec<f64> = (0..self.resolution)
+            .map(|i| variable.min + step * i as f64)
+            .collect();
+        let mus: Vec<f64> = xs
+            .iter()
+            .map(|&x| {
+                clipped
+                    .iter()
+                    .map(|(strength, m)| strength.min(m.degree(x)))
+                    .fold(0.0, f64::max)
+            })
+            .collect();
+        defuzzify(self.defuzzification, &xs, &mus).ok_or_else(|| {
+            CognitionError::ReasoningError(format!("no rule fired for output '{}'", output))
+        })
+    }
+
+    fn sugeno(
+        output: &str,
+        consequents: &[(f64, &FuzzyConsequent)],
+        inputs: &HashMap<String, f64>,
+    ) -> Result<f64, CognitionError> {
+        let mut weighted = 0.0;
+        let mut total = 0.0;
+        for (strength, consequent) in consequents {
+            if let FuzzyConsequent::Function {
+                constant,
+                coefficients,
+                ..
+            } = consequent
+            {
+                let mut z = *constant;
+                for (input, coefficient) in coefficients {
+                    let x = inputs.get(input).ok_or_else(|| {
+                        CognitionError::ReasoningError(format!("missing input '{}'", input))
+                    })?;
+                    z += coefficient * x;
+                }
+                weighted += strength * z;
+                total += strength;
+            }
+        }
+        if total == 0.0 {
+            return Err(CognitionError::ReasoningError(format!(
+                "no rule fired for output '{}'",
+                output
+            )));
+        }
+        Ok(weighted / total)
+    }
+}
+
+impl Default for FuzzySystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Crisp value of a sampled fuzzy set; `None` if the set is empty
+fn defuzzify(method: Defuzzification, xs: &[f64], mus: &[f64]) -> Option<f64> {
+    let area: f64 = mus.iter().sum();
+    if area <= f64::EPSILON {
+        return None;
+    }
+    match method {
+        Defuzzification::Centroid => {
+            Some(xs.iter().zip(mus).map(|(x, mu)| x * mu).sum::<f64>() / area)
+        }
+        Defuzzification::Bisector => {
+            let mut running = 0.0;
+            xs.iter()
+                .zip(mus)
+                .find(|(_, mu)| {
+                    running += *mu;
+                    running >= area / 2.0
+                })
+                .map(|(x, _)| *x)
+        }
+        Defuzzification::MeanOfMaxima => {
+            let peak = mus.iter().copied().fold(0.0, f64::max);
+            let maxima: Vec<f64> = xs
+                .iter()
+                .zip(mus)
+                .filter(|(_, mu)| (peak - **mu).abs() < 1e-9)
+                .map(|(x, _)| *x)
+                .collect();
+            Some(maxima.iter().sum::<f64>() / maxima.len() as f64)
+        }
+    }
+}
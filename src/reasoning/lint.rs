@@ -0,0 +1,437 @@
+use super::builtin;
+use super::query::rename;
+use super::{unify, Rule, Substitution, Term};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth knowing; often intended
+    Info,
+    /// Probably a mistake
+    Warning,
+    /// The rule cannot work as written
+    Error,
+}
+
+/// What a diagnostic reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// A conclusion or comparison uses a variable no fact condition binds
+    UnboundVariable,
+    /// Some condition can never be matched by a fact or derived conclusion
+    UnreachableRule,
+    /// Another rule concludes as much from no more conditions
+    SubsumedRule,
+    /// Rules whose conclusions feed back into their own conditions
+    RecursiveCycle,
+    /// No rule condition uses the conclusion
+    UnusedConclusion,
+    /// A `not` condition, which only `DatalogProgram` evaluates
+    NegatedCondition,
+}
+
+impl LintKind {
+    /// Get the short name
+    pub fn code(self) -> &'static str {
+        match self {
+            LintKind::UnboundVariable => "unbound-variable",
+            LintKind::UnreachableRule => "unreachable-rule",
+            LintKind::SubsumedRule => "subsumed-rule",
+            LintKind::RecursiveCycle => "recursive-cycle",
+            LintKind::UnusedConclusion => "unused-conclusion",
+            LintKind::NegatedCondition => "negated-condition",
+        }
+    }
+
+    fn severity(self) -> Severity {
+        match self {
+            LintKind::UnboundVariable | LintKind::NegatedCondition => Severity::Error,
+            LintKind::UnreachableRule | LintKind::SubsumedRule => Severity::Warning,
+            LintKind::RecursiveCycle | LintKind::UnusedConclusion => Severity::Info,
+        }
+    }
+}
+
+/// Finding about a rule base
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    kind: LintKind,
+    rules: Vec<String>,
+    message: String,
+}
+
+impl Diagnostic {
+    fn new(kind: LintKind, rules: Vec<String>, message: String) -> Self {
+        Self {
+            kind,
+            rules,
+            message,
+        }
+    }
+
+    /// Get the kind
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    /// Get the severity
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
+    /// Get the names of the rules involved
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    /// Get the message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity() {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}[{}] {}: {}",
+            severity,
+            self.kind.code(),
+            self.rules.join(", "),
+            self.message
+        )
+    }
+}
+
+/// Rule split into fact conditions, negated conditions and comparisons
+struct Analysed<'a> {
+    rule: &'a Rule,
+    positive: Vec<Term>,
+    negated: Vec<Term>,
+    comparisons: Vec<Term>,
+    conclusions: Vec<Term>,
+}
+
+impl<'a> Analysed<'a> {
+    fn new(rule: &'a Rule) -> Self {
+        let mut analysed = Self {
+            rule,
+            positive: Vec::new(),
+            negated: Vec::new(),
+            comparisons: Vec::new(),
+            conclusions: rule.conclusion_terms(),
+        };
+        for condition in rule.conditions() {
+            if let Some(rest) = condition.trim().strip_prefix("not ") {
+                analysed.negated.push(Term::parse_lenient(rest));
+                continue;
+            }
+            let term = Term::parse_lenient(condition);
+            if builtin::is_builtin(&term) {
+                analysed.comparisons.push(term);
+            } else {
+                analysed.positive.push(term);
+            }
+        }
+        analysed
+    }
+
+    fn name(&self) -> String {
+        self.rule.name().to_string()
+    }
+}
+
+/// Check a rule base for likely mistakes, given the facts it starts from
+///
+/// Diagnostics are ordered by check: unbound variables, unreachable rules,
+/// subsumed rules, recursive cycles, then unused conclusions. Conditions
+/// written `not p` count as consuming `p` but bind no variables.
+pub fn lint_rules(rules: &[Rule], facts: &[Term]) -> Vec<Diagnostic> {
+    let analysed: Vec<Analysed> = rules.iter().map(Analysed::new).collect();
+    let mut diagnostics = Vec::new();
+    unbound_variables(&analysed, &mut diagnostics);
+    unreachable_rules(&analysed, facts, &mut diagnostics);
+    subsumed_rules(&analysed, &mut diagnostics);
+    recursive_cycles(&analysed, &mut diagnostics);
+    unused_conclusions(&analysed, &mut diagnostics);
+    diagnostics
+}
+
+/// Flag `not` conditions, for rule bases run by `ReasoningEngine`
+pub(crate) fn negated_conditions(rules: &[Rule]) -> Vec<Diagnostic> {
+    rules
+        .iter()
+        .map(Analysed::new)
+        .flat_map(|rule| {
+            rule.negated
+                .iter()
+                .map(|term| {
+                    Diagnostic::new(
+                        LintKind::NegatedCondition,
+                        vec![rule.name()],
+                        format!(
+                            "condition `not {}` is never evaluated, so the rule never fires; \
+                             use DatalogProgram for negation",
+                            term
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn unbound_variables(rules: &[Analysed], diagnostics: &mut Vec<Diagnostic>) {
+    for rule in rules {
+        let bound: HashSet<&str> = rule.positive.iter().flat_map(Term::variables).collect();
+        let unbound = |terms: &[Term], place: &str, diagnostics: &mut Vec<Diagnostic>| {
+            for term in terms {
+                for var in term.variables() {
+                    if !bound.contains(var) && !var.starts_with("_G") {
+                        diagnostics.push(Diagnostic::new(
+                            LintKind::UnboundVariable,
+                            vec![rule.name()],
+                            format!("variable {} in {} `{}` is never bound", var, place, term),
+                        ));
+                    }
+                }
+            }
+        };
+        unbound(&rule.conclusions, "conclusion", diagnostics);
+        unbound(&rule.comparisons, "comparison", diagnostics);
+        unbound(&rule.negated, "negated condition", diagnostics);
+    }
+}
+
+fn unreachable_rules(rules: &[Analysed], facts: &[Term], diagnostics: &mut Vec<Diagnostic>) {
+    let mut derivable: Vec<Term> = facts.to_vec();
+    let mut reachable = vec![false; rules.len()];
+    let mut renames = 0;
+    loop {
+        let mut changed = false;
+        for (i, rule) in rules.iter().enumerate() {
+            if reachable[i] || !rule.positive.iter().all(|c| matches_any(c, &derivable)) {
+                continue;
+            }
+            reachable[i] = true;
+            changed = true;
+            renames += 1;
+            derivable.extend(rule.conclusions.iter().map(|c| rename(c, renames)));
+        }
+        if !changed {
+            break;
+        }
+    }
+    for (rule, _) in rules.iter().zip(&reachable).filter(|(_, r)| !**r) {
+        let missing: Vec<String> = rule
+            .positive
+            .iter()
+            .filter(|c| !matches_any(c, &derivable))
+            .map(Term::to_string)
+            .collect();
+        diagnostics.push(Diagnostic::new(
+            LintKind::UnreachableRule,
+            vec![rule.name()],
+            format!(
+                "no fact or rule provides {}",
+                missing
+                    .iter()
+                    .map(|c| format!("`{}`", c))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+}
+
+fn matches_any(condition: &Term, derivable: &[Term]) -> bool {
+    let condition = rename(condition, 0);
+    derivable
+        .iter()
+        .any(|d| unify(&condition, d, &Substitution::new()).is_some())
+}
+
+fn subsumed_rules(rules: &[Analysed], diagnostics: &mut Vec<Diagnostic>) {
+    for (i, rule) in rules.iter().enumerate() {
+        let subsumer = rules.iter().enumerate().find(|(j, other)| {
+            *j != i && subsumes(other, rule) && (*j < i || !subsumes(rule, other))
+        });
+        if let Some((_, other)) = subsumer {
+            diagnostics.push(Diagnostic::new(
+                LintKind::SubsumedRule,
+                vec![rule.name(), other.name()],
+                format!(
+                    "rule '{}' derives everything rule '{}' does",
+                    other.name(),
+                    rule.name()
+                ),
+            ));
+        }
+    }
+}
+
+/// Check if `general` concludes everything `specific` does whenever it fires
+fn subsumes(general: &Analysed, specific: &Analysed) -> bool {
+    // Variables of the specific rule act as constants
+    let frozen = |terms: &[Term]| terms.iter().map(freeze).collect::<Vec<_>>();
+    let renamed = |terms: &[Term]| terms.iter().map(|t| rename(t, 0)).collect::<Vec<_>>();
+    let conditions = frozen(&specific.positive);
+    let negated = frozen(&specific.negated);
+    let comparisons = frozen(&specific.comparisons);
+    let conclusions = frozen(&specific.conclusions);
+    let general_negated = renamed(&general.negated);
+    let general_comparisons = renamed(&general.comparisons);
+    let general_conclusions = renamed(&general.conclusions);
+    // Negations and comparisons of the general rule must also guard the specific one
+    let guarded = |bindings: &Substitution, patterns: &[Term], targets: &[Term]| {
+        patterns
+            .iter()
+            .all(|p| targets.contains(&bindings.apply(p)))
+    };
+    embeddings(
+        &renamed(&general.positive),
+        &conditions,
+        Substitution::new(),
+    )
+    .into_iter()
+    .any(|bindings| {
+        guarded(&bindings, &general_negated, &negated)
+            && guarded(&bindings, &general_comparisons, &comparisons)
+            && conclusions
+                .iter()
+                .all(|c| general_conclusions.iter().any(|g| bindings.apply(g) == *c))
+    })
+}
+
+/// Substitutions mapping every pattern onto one of the targets
+fn embeddings(patterns: &[Term], targets: &[Term], bindings: Substitution) -> Vec<Substitution> {
+    let Some((first, rest)) = patterns.split_first() else {
+        return vec![bindings];
+    };
+    targets
+        .iter()
+        .filter_map(|t| unify(&bindings.apply(first), t, &bindings))
+        .flat_map(|b| embeddings(rest, targets, b))
+        .collect()
+}
+
+fn freeze(term: &Term) -> Term {
+    match term {
+        Term::Var(name) => Term::atom(format!("${}", name)),
+        Term::Compound(f, args) => Term::compound(f.clone(), args.iter().map(freeze).collect()),
+        Term::Atom(_) => term.clone(),
+    }
+}
+
+type Predicate = (String, usize);
+
+fn predicate(term: &Term) -> Option<Predicate> {
+    term.functor().map(|(f, n)| (f.to_string(), n))
+}
+
+fn recursive_cycles(rules: &[Analysed], diagnostics: &mut Vec<Diagnostic>) {
+    // Predicate dependency graph: condition predicate -> conclusion predicate
+    let mut edges: HashMap<Predicate, HashSet<Predicate>> = HashMap::new();
+    for rule in rules {
+        for condition in rule.positive.iter().chain(&rule.negated) {
+            for conclusion in &rule.conclusions {
+                if let (Some(from), Some(to)) = (predicate(condition), predicate(conclusion)) {
+                    edges.entry(from).or_default().insert(to);
+                }
+            }
+        }
+    }
+    let reaches = |from: &Predicate, to: &Predicate| {
+        let mut stack = vec![from.clone()];
+        let mut seen = HashSet::new();
+        while let Some(p) = stack.pop() {
+            for next in edges.get(&p).into_iter().flatten() {
+                if next == to {
+                    return true;
+                }
+                if seen.insert(next.clone()) {
+                    stack.push(next.clone());
+                }
+            }
+        }
+        false
+    };
+
+    // Group recursive rules by the cycle (strongly connected set) they sit on
+    let mut cycles: Vec<(Vec<Predicate>, Vec<String>)> = Vec::new();
+    for rule in rules {
+        for conclusion in rule.conclusions.iter().filter_map(predicate) {
+            let recursive = rule
+                .positive
+                .iter()
+                .chain(&rule.negated)
+                .filter_map(predicate)
+                .any(|c| reaches(&conclusion, &c));
+            if !recursive {
+                continue;
+            }
+            let mut members: Vec<Predicate> = edges
+                .keys()
+                .filter(|p| {
+                    **p == conclusion || (reaches(&conclusion, p) && reaches(p, &conclusion))
+                })
+                .cloned()
+                .collect();
+            members.sort();
+            match cycles.iter_mut().find(|(m, _)| *m == members) {
+                Some((_, names)) if names.contains(&rule.name()) => {}
+                Some((_, names)) => names.push(rule.name()),
+                None => cycles.push((members, vec![rule.name()])),
+            }
+        }
+    }
+    for (members, names) in cycles {
+        let path = members
+            .iter()
+            .map(|(f, n)| format!("{}/{}", f, n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        diagnostics.push(Diagnostic::new(
+            LintKind::RecursiveCycle,
+            names,
+            format!("recursion through {}", path),
+        ));
+    }
+}
+
+fn unused_conclusions(rules: &[Analysed], diagnostics: &mut Vec<Diagnostic>) {
+    let consumed: Vec<&Term> = rules
+        .iter()
+        .flat_map(|r| r.positive.iter().chain(&r.negated))
+        .collect();
+    for rule in rules {
+        for conclusion in &rule.conclusions {
+            let conclusion = rename(conclusion, 0);
+            let used = consumed
+                .iter()
+                .any(|c| unify(&conclusion, c, &Substitution::new()).is_some());
+            if !used {
+                diagnostics.push(Diagnostic::new(
+                    LintKind::UnusedConclusion,
+                    vec![rule.name()],
+                    format!("no rule uses `{}`", conclusion_text(rule, &conclusion)),
+                ));
+            }
+        }
+    }
+}
+
+/// Conclusion text without the renaming suffixes
+fn conclusion_text(rule: &Analysed, renamed: &Term) -> String {
+    rule.conclusions
+        .iter()
+        .find(|c| rename(c, 0) == *renamed)
+        .map_or_else(|| renamed.to_string(), Term::to_string)
+}
@@ -0,0 +1,222 @@
+use super::builtin;
+use super::engine::CompiledRule;
+use super::{unify, Substitution, Term};
+use std::collections::{HashMap, HashSet};
+
+/// Identifier of a fact in working memory; increases with every assertion
+pub type FactId = u64;
+
+/// How `ReasoningEngine` finds rules whose conditions are satisfied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchStrategy {
+    /// Re-scan every rule against all facts on each inference pass
+    Naive,
+    /// Keep matches up to date as facts are asserted and retracted
+    #[default]
+    Incremental,
+}
+
+/// A rule instantiation whose conditions are all satisfied
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Activation {
+    pub(crate) rule: usize,
+    pub(crate) facts: Vec<FactId>,
+    pub(crate) bindings: Substitution,
+    pub(crate) sequence: u64,
+}
+
+/// TREAT-style incremental matcher
+///
+/// Each rule condition has an alpha memory holding the facts it matches in
+/// isolation. Asserting a fact joins it against the other alpha memories of
+/// the same rule to find new instantiations; retracting a fact drops the
+/// instantiations that used it. Joins are not cached between changes, which
+/// keeps retraction cheap.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IncrementalMatcher {
+    alpha: Vec<Vec<Vec<(FactId, Term)>>>,
+    tokens: Vec<HashSet<Vec<FactId>>>,
+    pending: Vec<Activation>,
+    sequence: u64,
+    /// Conditions (rule, position) by functor and arity
+    dispatch: HashMap<(String, usize), Vec<(usize, usize)>>,
+    /// Conditions whose alpha memory holds each fact
+    memberships: HashMap<FactId, Vec<(usize, usize)>>,
+}
+
+impl IncrementalMatcher {
+    /// Register a rule and match it against the current facts
+    pub(crate) fn add_rule<'a, I>(&mut self, rule: &CompiledRule, facts: I)
+    where
+        I: IntoIterator<Item = (FactId, &'a Term)>,
+    {
+        let index = self.alpha.len();
+        for (position, condition) in rule.conditions.iter().enumerate() {
+            if builtin::is_builtin(condition) {
+                continue;
+            }
+            if let Some((name, arity)) = condition.functor() {
+                self.dispatch
+                    .entry((name.to_string(), arity))
+                    .or_default()
+                    .push((index, position));
+            }
+        }
+
+        let mut memories = vec![Vec::new(); rule.conditions.len()];
+        for (id, fact) in facts {
+            for (position, condition) in rule.conditions.iter().enumerate() {
+                if builtin::is_builtin(condition) {
+                    continue;
+                }
+                if unify(condition, fact, &Substitution::new()).is_some() {
+                    memories[position].push((id, fact.clone()));
+                    self.memberships
+                        .entry(id)
+                        .or_default()
+                        .push((index, position));
+                }
+            }
+        }
+        self.alpha.push(memories);
+        self.tokens.push(HashSet::new());
+
+        let seeds = vec![(vec![None; rule.conditions.len()], Substitution::new())];
+        for (facts, bindings) in self.join(index, rule, seeds, None) {
+            self.activate(index, facts, bindings);
+        }
+    }
+
+    /// Update matches after a fact was added to working memory
+    pub(crate) fn assert(&mut self, rules: &[CompiledRule], id: FactId, fact: &Term) {
+        let Some((name, arity)) = fact.functor() else {
+            return;
+        };
+        let Some(conditions) = self.dispatch.get(&(name.to_string(), arity)).cloned() else {
+            return;
+        };
+        for (index, position) in conditions {
+            let rule = &rules[index];
+            let condition = &rule.conditions[position];
+            if !constants_match(condition, fact) {
+                continue;
+            }
+            let Some(bindings) = unify(condition, fact, &Substitution::new()) else {
+                continue;
+            };
+            self.alpha[index][position].push((id, fact.clone()));
+            self.memberships
+                .entry(id)
+                .or_default()
+                .push((index, position));
+
+            let mut slots = vec![None; rule.conditions.len()];
+            slots[position] = Some(id);
+            for (facts, bindings) in self.join(index, rule, vec![(slots, bindings)], Some(position))
+            {
+                self.activate(index, facts, bindings);
+            }
+        }
+    }
+
+    /// Update matches after a fact was removed from working memory
+    pub(crate) fn retract(&mut self, id: FactId) {
+        let Some(conditions) = self.memberships.remove(&id) else {
+            return;
+        };
+        for (index, position) in conditions {
+            self.alpha[index][position].retain(|(fact, _)| *fact != id);
+            self.tokens[index].retain(|facts| !facts.contains(&id));
+        }
+        self.pending.retain(|a| !a.facts.contains(&id));
+    }
+
+    /// Activations that have not fired yet
+    pub(crate) fn pending(&self) -> &[Activation] {
+        &self.pending
+    }
+
+    /// Remove a pending activation so it can fire
+    pub(crate) fn take(&mut self, index: usize) -> Activation {
+        self.pending.remove(index)
+    }
+
+    /// Forget all facts and matches, keeping the rules
+    pub(crate) fn clear_facts(&mut self) {
+        for memories in &mut self.alpha {
+            memories.iter_mut().for_each(Vec::clear);
+        }
+        self.tokens.iter_mut().for_each(HashSet::clear);
+        self.pending.clear();
+        self.memberships.clear();
+    }
+
+    /// Record a new instantiation; tokens seen before are never re-activated
+    /// (refraction)
+    fn activate(&mut self, rule: usize, facts: Vec<FactId>, bindings: Substitution) {
+        if self.tokens[rule].insert(facts.clone()) {
+            self.sequence += 1;
+            self.pending.push(Activation {
+                rule,
+                facts,
+                bindings,
+                sequence: self.sequence,
+            });
+        }
+    }
+
+    /// Extend partial matches over every condition except `seeded`
+    ///
+    /// Comparisons have no alpha memory; they filter the complete matches.
+    fn join(
+        &self,
+        rule: usize,
+        compiled: &CompiledRule,
+        mut partial: Vec<(Vec<Option<FactId>>, Substitution)>,
+        seeded: Option<usize>,
+    ) -> Vec<(Vec<FactId>, Substitution)> {
+        for (position, condition) in compiled.conditions.iter().enumerate() {
+            if Some(position) == seeded || builtin::is_builtin(condition) {
+                continue;
+            }
+            let mut next = Vec::new();
+            for (slots, bindings) in &partial {
+                let pattern = bindings.apply(condition);
+                for (id, fact) in &self.alpha[rule][position] {
+                    if let Some(extended) = unify(&pattern, fact, bindings) {
+                        let mut slots = slots.clone();
+                        slots[position] = Some(*id);
+                        next.push((slots, extended));
+                    }
+                }
+            }
+            if next.is_empty() {
+                return Vec::new();
+            }
+            partial = next;
+        }
+        partial
+            .into_iter()
+            .filter(|(_, bindings)| {
+                compiled
+                    .conditions
+                    .iter()
+                    .filter(|c| builtin::is_builtin(c))
+                    .all(|c| builtin::holds(c, bindings))
+            })
+            .map(|(slots, bindings)| (slots.into_iter().flatten().collect(), bindings))
+            .collect()
+    }
+}
+
+/// Cheap alpha test: constants in the pattern agree with the fact
+fn constants_match(pattern: &Term, fact: &Term) -> bool {
+    match (pattern, fact) {
+        (Term::Var(_), _) => true,
+        (Term::Atom(a), Term::Atom(b)) => a == b,
+        (Term::Compound(f, xs), Term::Compound(g, ys)) => {
+            f == g && xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| constants_match(x, y))
+        }
+        _ => false,
+    }
+}
@@ -1,7 +1,48 @@
-﻿//! Reasoning module for logical inference
+//! Reasoning module for logical inference
 
+pub mod abduction;
+pub mod agenda;
+pub mod argumentation;
+pub mod bayes;
+mod builtin;
+pub mod cbr;
+pub mod certainty;
+pub mod csp;
+pub mod datalog;
+pub mod defeasible;
+pub mod dsl;
 pub mod engine;
+pub mod fuzzy;
+pub mod lint;
+pub mod matcher;
+pub mod ontology;
+pub mod query;
 pub mod rule;
+pub mod temporal;
+pub mod term;
+pub mod tms;
 
+pub use abduction::{Abduction, AbductiveExplanation};
+pub use agenda::ConflictStrategy;
+pub use argumentation::{
+    Argument, ArgumentSet, ArgumentationFramework, AttackKind, Defeat, Resolution, Semantics,
+};
+pub use bayes::{BayesNode, BayesianNetwork, Distribution, Evidence};
+pub use cbr::{substitute_parameters, Adaptation, Case, CaseBase, Outcome, Retention, Similarity};
+pub use certainty::{combine_evidence, CertaintyCombination};
+pub use csp::{Csp, Relation, SumBound};
+pub use datalog::{Aggregate, DatalogModel, DatalogProgram};
+pub use defeasible::DefeasibleModel;
+pub use dsl::{load_rules, parse_rules, SyntaxError};
 pub use engine::ReasoningEngine;
-pub use rule::Rule;
+pub use fuzzy::{
+    Defuzzification, FuzzyConsequent, FuzzyRule, FuzzySystem, LinguisticVariable, Membership,
+};
+pub use lint::{lint_rules, Diagnostic, LintKind, Severity};
+pub use matcher::{FactId, MatchStrategy};
+pub use ontology::{DisjointnessViolation, Ontology};
+pub use query::{ProofStep, ProofTree, QueryOptions, Solution, Solutions};
+pub use rule::{Rule, RuleKind};
+pub use temporal::{Monitor, TemporalFormula, Verdict, Violation};
+pub use term::{unify, Substitution, Term};
+pub use tms::Justification;
@@ -0,0 +1,293 @@
+use super::Term;
+use crate::CognitionError;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+/// Entity that belongs to two disjoint classes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisjointnessViolation {
+    entity: String,
+    first: String,
+    second: String,
+}
+
+impl DisjointnessViolation {
+    /// Get the class or instance in both classes
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+
+    /// Get the disjoint classes
+    pub fn classes(&self) -> (&str, &str) {
+        (&self.first, &self.second)
+    }
+}
+
+/// Class hierarchy with instances and inherited properties
+///
+/// Classes may have several superclasses. Properties set on a class are
+/// inherited by its subclasses and instances; the value set closest to the
+/// entity wins, and among equally close classes the first declared wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ontology {
+    classes: Vec<String>,
+    superclasses: BTreeMap<String, Vec<String>>,
+    instances: Vec<String>,
+    memberships: BTreeMap<String, Vec<String>>,
+    properties: BTreeMap<String, BTreeMap<String, String>>,
+    disjoint: Vec<(String, String)>,
+}
+
+impl Ontology {
+    /// Create an empty ontology
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a class; adding a known class has no effect
+    pub fn add_class(&mut self, class: impl Into<String>) {
+        let class = class.into();
+        if !self.classes.contains(&class) {
+            self.superclasses.insert(class.clone(), Vec::new());
+            self.classes.push(class);
+        }
+    }
+
+    /// Declare `class` a subclass of `superclass`, adding either if unknown
+    ///
+    /// Fails if the hierarchy would become cyclic.
+    pub fn add_subclass(
+        &mut self,
+        class: impl Into<String>,
+        superclass: impl Into<String>,
+    ) -> Result<(), CognitionError> {
+        let (class, superclass) = (class.into(), superclass.into());
+        if self.is_subclass_of(&superclass, &class) {
+            return Err(CognitionError::ReasoningError(format!(
+                "'{}' is-a '{}' would make the hierarchy cyclic",
+                class, superclass
+            )));
+        }
+        self.add_class(class.clone());
+        self.add_class(superclass.clone());
+        let parents = self.superclasses.entry(class).or_default();
+        if !parents.contains(&superclass) {
+            parents.push(superclass);
+        }
+        Ok(())
+    }
+
+    /// Make `instance` a member of `class`, adding the class if unknown
+    pub fn add_instance(&mut self, instance: impl Into<String>, class: impl Into<String>) {
+        let (instance, class) = (instance.into(), class.into());
+        self.add_class(class.clone());
+        if !self.instances.contains(&instance) {
+            self.instances.push(instance.clone());
+        }
+        let classes = self.memberships.entry(instance).or_default();
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+
+    /// Set a property of a class or instance, overriding inherited values
+    pub fn set_property(
+        &mut self,
+        entity: impl Into<String>,
+        property: impl Into<String>,
+        value: impl Into<String>,
+    ) {
+        self.properties
+            .entry(entity.into())
+            .or_default()
+            .insert(property.into(), value.into());
+    }
+
+    /// Declare two classes disjoint: nothing may belong to both
+    pub fn add_disjoint(&mut self, a: impl Into<String>, b: impl Into<String>) {
+        let (a, b) = (a.into(), b.into());
+        self.add_class(a.clone());
+        self.add_class(b.clone());
+        if !self
+            .disjoint
+            .iter()
+            .any(|(x, y)| (x == &a && y == &b) || (x == &b && y == &a))
+        {
+            self.disjoint.push((a, b));
+        }
+    }
+
+    /// Get all classes
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    /// Get all instances
+    pub fn instances(&self) -> &[String] {
+        &self.instances
+    }
+
+    /// Check if `class` is `ancestor` or one of its transitive subclasses
+    pub fn is_subclass_of(&self, class: &str, ancestor: &str) -> bool {
+        class == ancestor || self.superclasses_of(class).contains(&ancestor)
+    }
+
+    /// Transitive superclasses, nearest first
+    pub fn superclasses_of(&self, class: &str) -> Vec<&str> {
+        let start = self.superclasses.get(class).into_iter().flatten();
+        self.closure(start.map(String::as_str))
+    }
+
+    /// Transitive subclasses
+    pub fn subclasses_of(&self, class: &str) -> Vec<&str> {
+        self.classes
+            .iter()
+            .map(String::as_str)
+            .filter(|c| *c != class && self.is_subclass_of(c, class))
+            .collect()
+    }
+
+    /// Classes an instance belongs to, directly or by inheritance, nearest first
+    pub fn classes_of(&self, instance: &str) -> Vec<&str> {
+        let direct = self.memberships.get(instance).into_iter().flatten();
+        self.closure(direct.map(String::as_str))
+    }
+
+    /// Check if an instance belongs to a class, directly or by inheritance
+    pub fn is_instance_of(&self, instance: &str, class: &str) -> bool {
+        self.classes_of(instance).contains(&class)
+    }
+
+    /// Instances of a class or any of its subclasses
+    pub fn instances_of(&self, class: &str) -> Vec<&str> {
+        self.instances
+            .iter()
+            .map(String::as_str)
+            .filter(|i| self.is_instance_of(i, class))
+            .collect()
+    }
+
+    /// Value of a property for a class or instance, inherited if not set
+    pub fn property(&self, entity: &str, property: &str) -> Option<&str> {
+        let own = |e: &str| {
+            self.properties
+                .get(e)
+                .and_then(|p| p.get(property))
+                .map(String::as_str)
+        };
+        own(entity).or_else(|| self.lineage(entity).into_iter().find_map(own))
+    }
+
+    /// Every property of a class or instance, including inherited ones
+    pub fn properties(&self, entity: &str) -> BTreeMap<&str, &str> {
+        let mut properties = BTreeMap::new();
+        for source in std::iter::once(entity).chain(self.lineage(entity)) {
+            for (name, value) in self.properties.get(source).into_iter().flatten() {
+                properties.entry(name.as_str()).or_insert(value.as_str());
+            }
+        }
+        properties
+    }
+
+    /// Check if two classes are disjoint, directly or through superclasses
+    pub fn are_disjoint(&self, a: &str, b: &str) -> bool {
+        self.disjoint.iter().any(|(x, y)| {
+            (self.is_subclass_of(a, x) && self.is_subclass_of(b, y))
+                || (self.is_subclass_of(a, y) && self.is_subclass_of(b, x))
+        })
+    }
+
+    /// Classes and instances that belong to two disjoint classes
+    pub fn violations(&self) -> Vec<DisjointnessViolation> {
+        let mut violations = Vec::new();
+        let entities = self
+            .classes
+            .iter()
+            .map(|c| {
+                let mut lineage = vec![c.as_str()];
+                lineage.extend(self.superclasses_of(c));
+                (c, lineage)
+            })
+            .chain(self.instances.iter().map(|i| (i, self.classes_of(i))));
+        for (entity, classes) in entities {
+            for (first, second) in &self.disjoint {
+                if classes.contains(&first.as_str()) && classes.contains(&second.as_str()) {
+                    violations.push(DisjointnessViolation {
+                        entity: entity.clone(),
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// The ontology and its closure as facts for rule conditions
+    ///
+    /// Gives `subclass_of(Class, Ancestor)`, `instance_of(Instance, Class)`,
+    /// `property(Entity, Name, Value)` with inherited values, and
+    /// `disjoint(A, B)` in both directions.
+    pub fn to_terms(&self) -> Vec<Term> {
+        let atom = |name: &str| Term::atom(name);
+        let mut terms = Vec::new();
+        for class in &self.classes {
+            for ancestor in self.superclasses_of(class) {
+                terms.push(Term::compound(
+                    "subclass_of",
+                    vec![atom(class), atom(ancestor)],
+                ));
+            }
+        }
+        for instance in &self.instances {
+            for class in self.classes_of(instance) {
+                terms.push(Term::compound(
+                    "instance_of",
+                    vec![atom(instance), atom(class)],
+                ));
+            }
+        }
+        for entity in self.classes.iter().chain(&self.instances) {
+            for (name, value) in self.properties(entity) {
+                terms.push(Term::compound(
+                    "property",
+                    vec![atom(entity), atom(name), atom(value)],
+                ));
+            }
+        }
+        for (a, b) in &self.disjoint {
+            terms.push(Term::compound("disjoint", vec![atom(a), atom(b)]));
+            terms.push(Term::compound("disjoint", vec![atom(b), atom(a)]));
+        }
+        terms
+    }
+
+    /// Classes whose properties an entity inherits, nearest first
+    fn lineage(&self, entity: &str) -> Vec<&str> {
+        if self.memberships.contains_key(entity) {
+            self.classes_of(entity)
+        } else {
+            self.superclasses_of(entity)
+        }
+    }
+
+    /// Breadth-first closure over superclasses, including the start classes
+    fn closure<'a>(&'a self, start: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+        let mut queue: VecDeque<&str> = start.collect();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut order = Vec::new();
+        while let Some(class) = queue.pop_front() {
+            if !seen.insert(class) {
+                continue;
+            }
+            order.push(class);
+            queue.extend(
+                self.superclasses
+                    .get(class)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            );
+        }
+        order
+    }
+}
@@ -0,0 +1,535 @@
+use super::builtin;
+use super::{unify, ReasoningEngine, Substitution, Term};
+use crate::explanation::{Explanation, Reason};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Options for goal-directed queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryOptions {
+    max_depth: Option<usize>,
+    tabling: bool,
+}
+
+impl QueryOptions {
+    /// Create default options (unbounded depth, no tabling)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of nested rule applications
+    ///
+    /// Branches deeper than the limit are abandoned and the solution
+    /// iterator reports itself as truncated.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Memoise answers per subgoal so left-recursive rules terminate
+    ///
+    /// Tabled queries compute their answer tables on the first call to
+    /// `next`; the depth limit does not apply.
+    pub fn with_tabling(mut self, tabling: bool) -> Self {
+        self.tabling = tabling;
+        self
+    }
+
+    /// Get the depth limit
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Check if tabling is enabled
+    pub fn tabling(&self) -> bool {
+        self.tabling
+    }
+}
+
+/// What justified a step in a proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    /// Goal matched a known fact
+    Fact,
+    /// Goal matched the conclusion of the named rule
+    Rule(String),
+    /// Goal was a comparison that held
+    Builtin,
+}
+
+/// Explanation of how a goal was proved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofTree {
+    goal: Term,
+    step: ProofStep,
+    children: Vec<ProofTree>,
+}
+
+impl ProofTree {
+    pub(crate) fn new(goal: Term, step: ProofStep, children: Vec<ProofTree>) -> Self {
+        Self {
+            goal,
+            step,
+            children,
+        }
+    }
+
+    /// Get the proved goal
+    pub fn goal(&self) -> &Term {
+        &self.goal
+    }
+
+    /// Get the justification for this goal
+    pub fn step(&self) -> &ProofStep {
+        &self.step
+    }
+
+    /// Get the proofs of the rule's conditions
+    pub fn children(&self) -> &[ProofTree] {
+        &self.children
+    }
+
+    /// Names of every rule used in the proof, outermost first
+    pub fn rules_used(&self) -> Vec<&str> {
+        let mut rules = Vec::new();
+        self.collect_rules(&mut rules);
+        rules
+    }
+
+    fn collect_rules<'a>(&'a self, rules: &mut Vec<&'a str>) {
+        if let ProofStep::Rule(name) = &self.step {
+            rules.push(name);
+        }
+        self.children.iter().for_each(|c| c.collect_rules(rules));
+    }
+
+    /// Facts at the leaves of the proof
+    pub fn facts_used(&self) -> Vec<&Term> {
+        match self.step {
+            ProofStep::Fact => vec![&self.goal],
+            ProofStep::Rule(_) => self.children.iter().flat_map(|c| c.facts_used()).collect(),
+            ProofStep::Builtin => Vec::new(),
+        }
+    }
+
+    fn render(&self, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match &self.step {
+            ProofStep::Fact => writeln!(f, "{}{} [fact]", indent, self.goal)?,
+            ProofStep::Rule(name) => writeln!(f, "{}{} [rule {}]", indent, self.goal, name)?,
+            ProofStep::Builtin => writeln!(f, "{}{} [builtin]", indent, self.goal)?,
+        }
+        self.children
+            .iter()
+            .try_for_each(|c| c.render(depth + 1, f))
+    }
+}
+
+impl fmt::Display for ProofTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(0, f)
+    }
+}
+
+impl From<&ProofTree> for Explanation {
+    fn from(proof: &ProofTree) -> Self {
+        let reason = match &proof.step {
+            ProofStep::Fact => Reason::Fact,
+            ProofStep::Rule(name) => Reason::Rule(name.clone()),
+            ProofStep::Builtin => Reason::Builtin,
+        };
+        proof
+            .children
+            .iter()
+            .fold(Explanation::new(proof.goal.to_string(), reason), |e, c| {
+                e.with_child(c.into())
+            })
+    }
+}
+
+/// One answer to a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    bindings: Substitution,
+    proof: ProofTree,
+}
+
+impl Solution {
+    /// Value bound to a query variable
+    pub fn get(&self, var: &str) -> Option<&Term> {
+        self.bindings.get(var)
+    }
+
+    /// Bindings of the query variables
+    pub fn bindings(&self) -> &Substitution {
+        &self.bindings
+    }
+
+    /// Proof of the instantiated goal
+    pub fn proof(&self) -> &ProofTree {
+        &self.proof
+    }
+}
+
+/// Pending goal in a resolution branch
+#[derive(Debug, Clone)]
+struct Goal {
+    term: Term,
+    node: usize,
+    depth: usize,
+}
+
+/// Proof node under construction
+#[derive(Debug, Clone)]
+struct Node {
+    goal: Term,
+    step: Option<ProofStep>,
+    children: Vec<usize>,
+}
+
+/// Resolution branch: goals still to prove (next goal last) and bindings
+#[derive(Debug, Clone)]
+struct Branch {
+    goals: Vec<Goal>,
+    bindings: Substitution,
+    nodes: Vec<Node>,
+}
+
+/// Lazy iterator over the solutions of a query
+pub struct Solutions<'a> {
+    engine: &'a ReasoningEngine,
+    goal: Term,
+    options: QueryOptions,
+    branches: Vec<Branch>,
+    tabled: Option<std::vec::IntoIter<Solution>>,
+    renames: usize,
+    truncated: bool,
+}
+
+impl<'a> Solutions<'a> {
+    pub(crate) fn new(engine: &'a ReasoningEngine, goal: Term, options: QueryOptions) -> Self {
+        let root = Branch {
+            goals: vec![Goal {
+                term: goal.clone(),
+                node: 0,
+                depth: 0,
+            }],
+            bindings: Substitution::new(),
+            nodes: vec![Node {
+                goal: goal.clone(),
+                step: None,
+                children: Vec::new(),
+            }],
+        };
+        Self {
+            engine,
+            goal,
+            options,
+            branches: vec![root],
+            tabled: None,
+            renames: 0,
+            truncated: false,
+        }
+    }
+
+    /// Check if any branch was cut off by the depth limit so far
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    fn next_resolved(&mut self) -> Option<Solution> {
+        while let Some(mut branch) = self.branches.pop() {
+            let Some(goal) = branch.goals.pop() else {
+                return Some(self.solution(&branch));
+            };
+            if self.options.max_depth.is_some_and(|max| goal.depth > max) {
+                self.truncated = true;
+                continue;
+            }
+
+            let term = branch.bindings.apply(&goal.term);
+            if builtin::is_builtin(&term) {
+                if builtin::holds(&term, &branch.bindings) {
+                    branch.nodes[goal.node].step = Some(ProofStep::Builtin);
+                    self.branches.push(branch);
+                }
+                continue;
+            }
+            let mut alternatives = Vec::new();
+
+            for fact in self.engine.candidates(&term) {
+                if let Some(bindings) = unify(&term, fact, &branch.bindings) {
+                    let mut next = branch.clone();
+                    next.bindings = bindings;
+                    next.nodes[goal.node].step = Some(ProofStep::Fact);
+                    alternatives.push(next);
+                }
+            }
+
+            for rule in self.engine.compiled_rules() {
+                for conclusion in &rule.conclusions {
+                    self.renames += 1;
+                    let id = self.renames;
+                    let head = rename(conclusion, id);
+                    let Some(bindings) = unify(&term, &head, &branch.bindings) else {
+                        continue;
+                    };
+                    let mut next = branch.clone();
+                    next.bindings = bindings;
+                    next.nodes[goal.node].step = Some(ProofStep::Rule(rule.name.clone()));
+                    let mut body = Vec::new();
+                    for condition in &rule.conditions {
+                        let condition = rename(condition, id);
+                        let node = next.nodes.len();
+                        next.nodes.push(Node {
+                            goal: condition.clone(),
+                            step: None,
+                            children: Vec::new(),
+                        });
+                        next.nodes[goal.node].children.push(node);
+                        body.push(Goal {
+                            term: condition,
+                            node,
+                            depth: goal.depth + 1,
+                        });
+                    }
+                    // Comparisons wait for the other conditions to bind their
+                    // variables, as in forward chaining
+                    body.sort_by_key(|g| builtin::is_builtin(&g.term));
+                    next.goals.extend(body.into_iter().rev());
+                    alternatives.push(next);
+                }
+            }
+
+            self.branches.extend(alternatives.into_iter().rev());
+        }
+        None
+    }
+
+    fn solution(&self, branch: &Branch) -> Solution {
+        Solution {
+            bindings: query_bindings(&self.goal, &branch.bindings),
+            proof: build_proof(&branch.nodes, 0, &branch.bindings),
+        }
+    }
+}
+
+impl Iterator for Solutions<'_> {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Solution> {
+        if !self.options.tabling {
+            return self.next_resolved();
+        }
+        if self.tabled.is_none() {
+            let mut tabler = Tabler::new(self.engine);
+            let solutions: Vec<Solution> = tabler
+                .answers(&self.goal)
+                .into_iter()
+                .filter_map(|(answer, proof)| {
+                    let bindings = unify(&self.goal, &answer, &Substitution::new())?;
+                    Some(Solution {
+                        bindings: query_bindings(&self.goal, &bindings),
+                        proof,
+                    })
+                })
+                .collect();
+            self.tabled = Some(solutions.into_iter());
+        }
+        self.tabled.as_mut().and_then(Iterator::next)
+    }
+}
+
+/// Answers recorded for one subgoal variant
+#[derive(Debug, Default)]
+struct Table {
+    answers: Vec<(Term, ProofTree)>,
+    seen: HashSet<Term>,
+    complete: bool,
+}
+
+/// Tabled resolution: iterates each subgoal's answers to a fixpoint
+struct Tabler<'a> {
+    engine: &'a ReasoningEngine,
+    tables: HashMap<Term, Table>,
+    stack: Vec<Term>,
+    lowest_dependency: usize,
+    renames: usize,
+}
+
+impl<'a> Tabler<'a> {
+    fn new(engine: &'a ReasoningEngine) -> Self {
+        Self {
+            engine,
+            tables: HashMap::new(),
+            stack: Vec::new(),
+            lowest_dependency: usize::MAX,
+            renames: 0,
+        }
+    }
+
+    fn answers(&mut self, goal: &Term) -> Vec<(Term, ProofTree)> {
+        let key = variant_key(goal);
+        if let Some(table) = self.tables.get(&key) {
+            if table.complete {
+                return table.answers.clone();
+            }
+            if let Some(position) = self.stack.iter().position(|k| *k == key) {
+                // Recursive call: consume what the ancestor has found so far
+                self.lowest_dependency = self.lowest_dependency.min(position);
+                return table.answers.clone();
+            }
+        }
+
+        let position = self.stack.len();
+        self.stack.push(key.clone());
+        self.tables.entry(key.clone()).or_default();
+        let outer_dependency = std::mem::replace(&mut self.lowest_dependency, usize::MAX);
+
+        loop {
+            let found = self.resolve(goal);
+            let table = self.tables.entry(key.clone()).or_default();
+            let before = table.answers.len();
+            for (answer, proof) in found {
+                if table.seen.insert(variant_key(&answer)) {
+                    table.answers.push((answer, proof));
+                }
+            }
+            if table.answers.len() == before {
+                break;
+            }
+        }
+
+        self.stack.pop();
+        // Tables that read an unfinished ancestor are recomputed on the next call
+        let dependency = self.lowest_dependency;
+        let table = self.tables.entry(key).or_default();
+        table.complete = dependency >= position;
+        self.lowest_dependency = if dependency < position {
+            outer_dependency.min(dependency)
+        } else {
+            outer_dependency
+        };
+        table.answers.clone()
+    }
+
+    fn resolve(&mut self, goal: &Term) -> Vec<(Term, ProofTree)> {
+        let mut found = Vec::new();
+        if builtin::is_builtin(goal) {
+            if builtin::holds(goal, &Substitution::new()) {
+                found.push((
+                    goal.clone(),
+                    ProofTree {
+                        goal: goal.clone(),
+                        step: ProofStep::Builtin,
+                        children: Vec::new(),
+                    },
+                ));
+            }
+            return found;
+        }
+        for fact in self.engine.candidates(goal) {
+            if unify(goal, fact, &Substitution::new()).is_some() {
+                found.push((
+                    fact.clone(),
+                    ProofTree {
+                        goal: fact.clone(),
+                        step: ProofStep::Fact,
+                        children: Vec::new(),
+                    },
+                ));
+            }
+        }
+
+        let engine = self.engine;
+        for rule in engine.compiled_rules() {
+            for conclusion in &rule.conclusions {
+                self.renames += 1;
+                let id = self.renames;
+                let head = rename(conclusion, id);
+                let Some(bindings) = unify(goal, &head, &Substitution::new()) else {
+                    continue;
+                };
+                let mut partial = vec![(bindings, Vec::new())];
+                // Comparisons go last, once the other conditions have bound
+                // their variables, as in forward chaining
+                let mut order: Vec<usize> = (0..rule.conditions.len()).collect();
+                order.sort_by_key(|&i| builtin::is_builtin(&rule.conditions[i]));
+                for i in order {
+                    let condition = rename(&rule.conditions[i], id);
+                    let mut next = Vec::new();
+                    for (bindings, proofs) in partial {
+                        let subgoal = bindings.apply(&condition);
+                        for (answer, proof) in self.answers(&subgoal) {
+                            if let Some(extended) = unify(&subgoal, &answer, &bindings) {
+                                let mut proofs: Vec<(usize, ProofTree)> = proofs.clone();
+                                proofs.push((i, proof));
+                                next.push((extended, proofs));
+                            }
+                        }
+                    }
+                    partial = next;
+                }
+                for (bindings, mut proofs) in partial {
+                    proofs.sort_by_key(|(i, _)| *i);
+                    let children = proofs.into_iter().map(|(_, proof)| proof).collect();
+                    let answer = bindings.apply(goal);
+                    found.push((
+                        answer.clone(),
+                        ProofTree {
+                            goal: answer,
+                            step: ProofStep::Rule(rule.name.clone()),
+                            children,
+                        },
+                    ));
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Rename rule variables apart so each rule use gets fresh variables
+pub(crate) fn rename(term: &Term, id: usize) -> Term {
+    match term {
+        Term::Var(name) => Term::Var(format!("{}#{}", name, id)),
+        Term::Compound(functor, args) => Term::Compound(
+            functor.clone(),
+            args.iter().map(|a| rename(a, id)).collect(),
+        ),
+        Term::Atom(_) => term.clone(),
+    }
+}
+
+/// Canonical form of a term up to variable renaming
+fn variant_key(term: &Term) -> Term {
+    let mut canonical = Substitution::new();
+    for (i, var) in term.variables().into_iter().enumerate() {
+        canonical.bind(var, Term::Var(format!("_V{}", i)));
+    }
+    canonical.apply(term)
+}
+
+/// Resolved bindings restricted to the variables of the query
+fn query_bindings(goal: &Term, bindings: &Substitution) -> Substitution {
+    let mut result = Substitution::new();
+    for var in goal.variables() {
+        if let Some(value) = bindings.resolve(var) {
+            result.bind(var, value);
+        }
+    }
+    result
+}
+
+fn build_proof(nodes: &[Node], index: usize, bindings: &Substitution) -> ProofTree {
+    let node = &nodes[index];
+    ProofTree {
+        goal: bindings.apply(&node.goal),
+        step: node.step.clone().unwrap_or(ProofStep::Fact),
+        children: node
+            .children
+            .iter()
+            .map(|&c| build_proof(nodes, c, bindings))
+            .collect(),
+    }
+}
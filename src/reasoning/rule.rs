@@ -1,9 +1,27 @@
+use super::Term;
+
+/// How firmly a rule's conclusions follow from its conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleKind {
+    /// Conclusions always follow; used by forward and backward chaining
+    #[default]
+    Strict,
+    /// Conclusions follow unless defeated by a conflicting rule
+    Defeasible,
+    /// Only blocks conflicting conclusions; never supports its own
+    Defeater,
+}
+
 /// A reasoning rule
 #[derive(Debug, Clone)]
 pub struct Rule {
     name: String,
     conditions: Vec<String>,
     conclusions: Vec<String>,
+    salience: i32,
+    strength: f64,
+    kind: RuleKind,
+    superior_to: Vec<String>,
 }
 
 impl Rule {
@@ -13,6 +31,10 @@ impl Rule {
             name: name.into(),
             conditions: Vec::new(),
             conclusions: Vec::new(),
+            salience: 0,
+            strength: 1.0,
+            kind: RuleKind::Strict,
+            superior_to: Vec::new(),
         }
     }
 
@@ -28,6 +50,31 @@ impl Rule {
         self
     }
 
+    /// Set the salience; higher salience rules fire first
+    pub fn with_salience(mut self, salience: i32) -> Self {
+        self.salience = salience;
+        self
+    }
+
+    /// Set the strength: certainty of the conclusions when every condition
+    /// is certain (clamped to 0.0..=1.0)
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the kind
+    pub fn with_kind(mut self, kind: RuleKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Make this rule superior to the named rule when their conclusions conflict
+    pub fn with_superiority_over(mut self, rule: impl Into<String>) -> Self {
+        self.superior_to.push(rule.into());
+        self
+    }
+
     /// Get the name
     pub fn name(&self) -> &str {
         &self.name
@@ -42,4 +89,40 @@ impl Rule {
     pub fn conclusions(&self) -> &[String] {
         &self.conclusions
     }
+
+    /// Get the salience
+    pub fn salience(&self) -> i32 {
+        self.salience
+    }
+
+    /// Get the strength
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// Get the kind
+    pub fn kind(&self) -> RuleKind {
+        self.kind
+    }
+
+    /// Get the names of the rules this rule is superior to
+    pub fn superior_to(&self) -> &[String] {
+        &self.superior_to
+    }
+
+    /// Conditions parsed as terms (unparsable text becomes an opaque atom)
+    pub fn condition_terms(&self) -> Vec<Term> {
+        self.conditions
+            .iter()
+            .map(|c| Term::parse_lenient(c))
+            .collect()
+    }
+
+    /// Conclusions parsed as terms (unparsable text becomes an opaque atom)
+    pub fn conclusion_terms(&self) -> Vec<Term> {
+        self.conclusions
+            .iter()
+            .map(|c| Term::parse_lenient(c))
+            .collect()
+    }
 }
@@ -0,0 +1,556 @@
+use super::builtin;
+use super::term::TermParser;
+use super::{Substitution, SyntaxError, Term};
+use crate::bdi::BeliefBase;
+use std::fmt;
+
+/// Linear temporal logic formula over belief snapshots
+///
+/// Propositions are read from a snapshot: `carrying` holds when that belief
+/// exists and is not `false`, `status(idle)` when the belief has that value,
+/// and `battery < 10` compares a belief's value (missing beliefs make
+/// propositions false). Operators, from loosest to tightest binding:
+///
+/// - `->` (or `implies`), `||` (`or`), `&&` (`and`)
+/// - `until` (`U`) and `since` (`S`)
+/// - `!` (`not`), `next` (`X`), `always` (`G`), `eventually` (`F`),
+///   `previously` (`Y`), `historically` (`H`), `once` (`O`)
+///
+/// Past operators may only contain past operators and propositions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemporalFormula {
+    /// Always true
+    True,
+    /// Always false
+    False,
+    /// Proposition about the current snapshot
+    Prop(Term),
+    /// Negation
+    Not(Box<TemporalFormula>),
+    /// Conjunction
+    And(Box<TemporalFormula>, Box<TemporalFormula>),
+    /// Disjunction
+    Or(Box<TemporalFormula>, Box<TemporalFormula>),
+    /// Holds at the next snapshot
+    Next(Box<TemporalFormula>),
+    /// Holds from now on
+    Always(Box<TemporalFormula>),
+    /// Holds now or later
+    Eventually(Box<TemporalFormula>),
+    /// Left holds until right does, which must happen
+    Until(Box<TemporalFormula>, Box<TemporalFormula>),
+    /// Held at the previous snapshot (slot numbers the past operators)
+    Previously(usize, Box<TemporalFormula>),
+    /// Held at every snapshot so far
+    Historically(usize, Box<TemporalFormula>),
+    /// Held at some snapshot so far
+    Once(usize, Box<TemporalFormula>),
+    /// Right held at some point, and left at every snapshot since
+    Since(usize, Box<TemporalFormula>, Box<TemporalFormula>),
+}
+
+use TemporalFormula as F;
+
+impl TemporalFormula {
+    /// Parse a formula
+    pub fn parse(text: &str) -> Result<Self, SyntaxError> {
+        let mut parser = FormulaParser {
+            terms: TermParser::new(text),
+            slots: 0,
+        };
+        let formula = parser.implication()?;
+        parser.terms.skip_whitespace();
+        if !parser.terms.at_end() {
+            return Err(parser.terms.error("unexpected text after formula"));
+        }
+        Ok(formula)
+    }
+
+    fn is_past(&self) -> bool {
+        match self {
+            F::True | F::False | F::Prop(_) => true,
+            F::Not(a) | F::Previously(_, a) | F::Historically(_, a) | F::Once(_, a) => a.is_past(),
+            F::And(a, b) | F::Or(a, b) | F::Since(_, a, b) => a.is_past() && b.is_past(),
+            _ => false,
+        }
+    }
+
+    fn not(self) -> Self {
+        match self {
+            F::True => F::False,
+            F::False => F::True,
+            F::Not(a) => *a,
+            other => F::Not(Box::new(other)),
+        }
+    }
+
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (F::False, _) | (_, F::False) => F::False,
+            (F::True, x) | (x, F::True) => x,
+            (a, b) if a == b => a,
+            (a, b) => F::And(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (F::True, _) | (_, F::True) => F::True,
+            (F::False, x) | (x, F::False) => x,
+            (a, b) if a == b => a,
+            (a, b) => F::Or(Box::new(a), Box::new(b)),
+        }
+    }
+}
+
+impl fmt::Display for TemporalFormula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            F::True => write!(f, "true"),
+            F::False => write!(f, "false"),
+            F::Prop(term) => write!(f, "{}", term),
+            F::Not(a) => write!(f, "!{}", Paren(a)),
+            F::And(a, b) => write!(f, "{} && {}", Paren(a), Paren(b)),
+            F::Or(a, b) => write!(f, "{} || {}", Paren(a), Paren(b)),
+            F::Next(a) => write!(f, "next {}", Paren(a)),
+            F::Always(a) => write!(f, "always {}", Paren(a)),
+            F::Eventually(a) => write!(f, "eventually {}", Paren(a)),
+            F::Until(a, b) => write!(f, "{} until {}", Paren(a), Paren(b)),
+            F::Previously(_, a) => write!(f, "previously {}", Paren(a)),
+            F::Historically(_, a) => write!(f, "historically {}", Paren(a)),
+            F::Once(_, a) => write!(f, "once {}", Paren(a)),
+            F::Since(_, a, b) => write!(f, "{} since {}", Paren(a), Paren(b)),
+        }
+    }
+}
+
+/// Display wrapper adding parentheses around compound operands
+struct Paren<'a>(&'a TemporalFormula);
+
+impl fmt::Display for Paren<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            F::True | F::False | F::Prop(_) => write!(f, "{}", self.0),
+            other => write!(f, "({})", other),
+        }
+    }
+}
+
+struct FormulaParser<'a> {
+    terms: TermParser<'a>,
+    slots: usize,
+}
+
+impl FormulaParser<'_> {
+    fn slot(&mut self) -> usize {
+        self.slots += 1;
+        self.slots - 1
+    }
+
+    fn implication(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        let left = self.disjunction()?;
+        if self.terms.keyword_symbol("->") || self.terms.keyword("implies") {
+            let right = self.implication()?;
+            return Ok(F::Or(Box::new(F::Not(Box::new(left))), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn disjunction(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        let mut left = self.conjunction()?;
+        while self.terms.keyword_symbol("||") || self.terms.keyword("or") {
+            left = F::Or(Box::new(left), Box::new(self.conjunction()?));
+        }
+        Ok(left)
+    }
+
+    fn conjunction(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        let mut left = self.binary()?;
+        while self.terms.keyword_symbol("&&") || self.terms.keyword("and") {
+            left = F::And(Box::new(left), Box::new(self.binary()?));
+        }
+        Ok(left)
+    }
+
+    fn binary(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        let left = self.unary()?;
+        if self.terms.keyword("until") || self.terms.keyword("U") {
+            let right = self.binary()?;
+            return Ok(F::Until(Box::new(left), Box::new(right)));
+        }
+        let start = self.terms.position();
+        if self.terms.keyword("since") || self.terms.keyword("S") {
+            let right = self.binary()?;
+            let slot = self.past(start, [&left, &right])?;
+            return Ok(F::Since(slot, Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        self.terms.skip_whitespace();
+        let start = self.terms.position();
+        if self.terms.keyword_symbol("!") || self.terms.keyword("not") {
+            return Ok(F::Not(Box::new(self.unary()?)));
+        }
+        if self.terms.keyword("next") || self.terms.keyword("X") {
+            return Ok(F::Next(Box::new(self.unary()?)));
+        }
+        if self.terms.keyword("always") || self.terms.keyword("G") {
+            return Ok(F::Always(Box::new(self.unary()?)));
+        }
+        if self.terms.keyword("eventually") || self.terms.keyword("F") {
+            return Ok(F::Eventually(Box::new(self.unary()?)));
+        }
+        if self.terms.keyword("previously") || self.terms.keyword("Y") {
+            let operand = self.unary()?;
+            let slot = self.past(start, [&operand])?;
+            return Ok(F::Previously(slot, Box::new(operand)));
+        }
+        if self.terms.keyword("historically") || self.terms.keyword("H") {
+            let operand = self.unary()?;
+            let slot = self.past(start, [&operand])?;
+            return Ok(F::Historically(slot, Box::new(operand)));
+        }
+        if self.terms.keyword("once") || self.terms.keyword("O") {
+            let operand = self.unary()?;
+            let slot = self.past(start, [&operand])?;
+            return Ok(F::Once(slot, Box::new(operand)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<TemporalFormula, SyntaxError> {
+        self.terms.skip_whitespace();
+        if self.terms.peek() == Some('(') {
+            self.terms.bump();
+            let formula = self.implication()?;
+            self.terms.expect(')')?;
+            return Ok(formula);
+        }
+        if self.terms.keyword("true") {
+            return Ok(F::True);
+        }
+        if self.terms.keyword("false") {
+            return Ok(F::False);
+        }
+        let start = self.terms.position();
+        let proposition = self.terms.condition()?;
+        if !proposition.is_ground() {
+            return Err(self
+                .terms
+                .error_at(start, "propositions cannot contain variables"));
+        }
+        Ok(F::Prop(proposition))
+    }
+
+    /// Allocate a slot for a past operator over past-only operands
+    fn past<const N: usize>(
+        &mut self,
+        start: usize,
+        operands: [&TemporalFormula; N],
+    ) -> Result<usize, SyntaxError> {
+        if operands.iter().all(|o| o.is_past()) {
+            Ok(self.slot())
+        } else {
+            Err(self
+                .terms
+                .error_at(start, "past operators cannot contain future operators"))
+        }
+    }
+}
+
+/// Monitoring outcome so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Holds however the trace continues
+    Satisfied,
+    /// Fails however the trace continues
+    Violated,
+    /// Depends on snapshots not yet seen
+    Pending,
+}
+
+/// Property violation and the snapshot where it became certain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    property: String,
+    step: usize,
+}
+
+impl Violation {
+    /// Get the violated property
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    /// Get the 0-based snapshot index
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' violated at step {}", self.property, self.step)
+    }
+}
+
+/// State of a past operator between snapshots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PastSlot {
+    /// Value at the latest snapshot
+    now: bool,
+    /// Operand value at the latest snapshot, for `previously`
+    operand: bool,
+}
+
+/// Online monitor checking a formula against successive snapshots
+///
+/// The formula must hold at the first snapshot, so invariants are written
+/// `always ...`. Future operators are checked by rewriting the formula after
+/// every snapshot into what the rest of the trace must satisfy, and past
+/// operators by updating a summary of the history, so memory does not grow
+/// with the trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    property: String,
+    formula: TemporalFormula,
+    residual: TemporalFormula,
+    slots: Vec<PastSlot>,
+    step: usize,
+    violation: Option<Violation>,
+}
+
+impl Monitor {
+    /// Create a monitor for a formula
+    pub fn new(property: &str) -> Result<Self, SyntaxError> {
+        let formula = TemporalFormula::parse(property)?;
+        Ok(Self::from_formula(formula))
+    }
+
+    /// Create a monitor for a parsed formula
+    pub fn from_formula(formula: TemporalFormula) -> Self {
+        let slots = vec![PastSlot::default(); slot_count(&formula)];
+        // A formula that is false outright is violated before any snapshot
+        let violation = (formula == F::False).then(|| Violation {
+            property: formula.to_string(),
+            step: 0,
+        });
+        Self {
+            property: formula.to_string(),
+            residual: formula.clone(),
+            formula,
+            slots,
+            step: 0,
+            violation,
+        }
+    }
+
+    /// Get the monitored formula
+    pub fn formula(&self) -> &TemporalFormula {
+        &self.formula
+    }
+
+    /// Get the number of snapshots observed
+    pub fn steps(&self) -> usize {
+        self.step
+    }
+
+    /// Get the violation, if one occurred
+    pub fn violation(&self) -> Option<&Violation> {
+        self.violation.as_ref()
+    }
+
+    /// Consume the next snapshot and return the verdict so far
+    pub fn observe(&mut self, beliefs: &BeliefBase) -> Verdict {
+        if self.residual != F::True && self.residual != F::False {
+            let first = self.step == 0;
+            let formula = self.formula.clone();
+            update(&formula, &mut self.slots, beliefs, first);
+            let residual = std::mem::replace(&mut self.residual, F::True);
+            self.residual = progress(residual, &self.slots, beliefs);
+            if self.residual == F::False {
+                self.violation = Some(Violation {
+                    property: self.property.clone(),
+                    step: self.step,
+                });
+            }
+        }
+        self.step += 1;
+        self.verdict()
+    }
+
+    /// Get the verdict so far
+    pub fn verdict(&self) -> Verdict {
+        match self.residual {
+            F::True => Verdict::Satisfied,
+            F::False => Verdict::Violated,
+            _ => Verdict::Pending,
+        }
+    }
+
+    /// Verdict if the trace ended now
+    ///
+    /// Pending `always` obligations hold; pending `eventually`, `until` and
+    /// `next` obligations fail.
+    pub fn finish(&self) -> Verdict {
+        if finished(&self.residual) {
+            Verdict::Satisfied
+        } else {
+            Verdict::Violated
+        }
+    }
+
+    /// Forget every snapshot observed
+    pub fn reset(&mut self) {
+        *self = Self::from_formula(self.formula.clone());
+    }
+}
+
+fn slot_count(formula: &TemporalFormula) -> usize {
+    match formula {
+        F::True | F::False | F::Prop(_) => 0,
+        F::Not(a) | F::Next(a) | F::Always(a) | F::Eventually(a) => slot_count(a),
+        F::And(a, b) | F::Or(a, b) | F::Until(a, b) => slot_count(a) + slot_count(b),
+        F::Previously(_, a) | F::Historically(_, a) | F::Once(_, a) => slot_count(a) + 1,
+        F::Since(_, a, b) => slot_count(a) + slot_count(b) + 1,
+    }
+}
+
+/// Update every past operator for the current snapshot; returns the value
+/// of past-only formulas (other results are unused)
+fn update(
+    formula: &TemporalFormula,
+    slots: &mut [PastSlot],
+    beliefs: &BeliefBase,
+    first: bool,
+) -> bool {
+    match formula {
+        F::True => true,
+        F::False => false,
+        F::Prop(term) => proposition(term, beliefs),
+        F::Not(a) => !update(a, slots, beliefs, first),
+        F::And(a, b) => {
+            let (a, b) = (
+                update(a, slots, beliefs, first),
+                update(b, slots, beliefs, first),
+            );
+            a && b
+        }
+        F::Or(a, b) => {
+            let (a, b) = (
+                update(a, slots, beliefs, first),
+                update(b, slots, beliefs, first),
+            );
+            a || b
+        }
+        F::Next(a) | F::Always(a) | F::Eventually(a) => {
+            update(a, slots, beliefs, first);
+            false
+        }
+        F::Until(a, b) => {
+            update(a, slots, beliefs, first);
+            update(b, slots, beliefs, first);
+            false
+        }
+        F::Previously(slot, a) => {
+            let operand = update(a, slots, beliefs, first);
+            let slot = &mut slots[*slot];
+            slot.now = !first && slot.operand;
+            slot.operand = operand;
+            slot.now
+        }
+        F::Historically(slot, a) => {
+            let operand = update(a, slots, beliefs, first);
+            let slot = &mut slots[*slot];
+            slot.now = operand && (first || slot.now);
+            slot.now
+        }
+        F::Once(slot, a) => {
+            let operand = update(a, slots, beliefs, first);
+            let slot = &mut slots[*slot];
+            slot.now = operand || (!first && slot.now);
+            slot.now
+        }
+        F::Since(slot, a, b) => {
+            let (a, b) = (
+                update(a, slots, beliefs, first),
+                update(b, slots, beliefs, first),
+            );
+            let slot = &mut slots[*slot];
+            slot.now = b || (a && !first && slot.now);
+            slot.now
+        }
+    }
+}
+
+/// What the rest of the trace must satisfy after the current snapshot
+fn progress(formula: TemporalFormula, slots: &[PastSlot], beliefs: &BeliefBase) -> TemporalFormula {
+    let truth = |value: bool| if value { F::True } else { F::False };
+    match formula {
+        F::True | F::False => formula,
+        F::Prop(ref term) => truth(proposition(term, beliefs)),
+        F::Previously(slot, _)
+        | F::Historically(slot, _)
+        | F::Once(slot, _)
+        | F::Since(slot, ..) => truth(slots[slot].now),
+        F::Not(a) => progress(*a, slots, beliefs).not(),
+        F::And(a, b) => progress(*a, slots, beliefs).and(progress(*b, slots, beliefs)),
+        F::Or(a, b) => progress(*a, slots, beliefs).or(progress(*b, slots, beliefs)),
+        F::Next(a) => *a,
+        F::Always(a) => {
+            let now = progress((*a).clone(), slots, beliefs);
+            now.and(F::Always(a))
+        }
+        F::Eventually(a) => {
+            let now = progress((*a).clone(), slots, beliefs);
+            now.or(F::Eventually(a))
+        }
+        F::Until(a, b) => {
+            let right = progress((*b).clone(), slots, beliefs);
+            let left = progress((*a).clone(), slots, beliefs);
+            right.or(left.and(F::Until(a, b)))
+        }
+    }
+}
+
+/// Whether a residual formula holds on an empty remainder of the trace
+fn finished(formula: &TemporalFormula) -> bool {
+    match formula {
+        F::True | F::Always(_) => true,
+        F::Not(a) => !finished(a),
+        F::And(a, b) => finished(a) && finished(b),
+        F::Or(a, b) => finished(a) || finished(b),
+        _ => false,
+    }
+}
+
+/// Evaluate a proposition against a snapshot
+fn proposition(term: &Term, beliefs: &BeliefBase) -> bool {
+    if let Some((_, left, right)) = builtin::comparison(term) {
+        let Term::Atom(key) = left else {
+            return false;
+        };
+        if !beliefs.contains(key) {
+            return false;
+        }
+        let resolve = |side: &Term| match side {
+            Term::Atom(key) => beliefs
+                .get(key)
+                .map_or_else(|| side.clone(), |b| Term::from(b.value())),
+            other => other.clone(),
+        };
+        let (left, right) = (resolve(left), resolve(right));
+        let functor = term.functor().map_or("", |(f, _)| f);
+        let resolved = Term::compound(functor, vec![left, right]);
+        return builtin::holds(&resolved, &Substitution::new());
+    }
+    match term {
+        Term::Atom(key) => beliefs.get(key).is_some_and(|b| b.as_bool() != Some(false)),
+        Term::Compound(key, args) if args.len() == 1 => beliefs
+            .get(key)
+            .is_some_and(|b| Term::from(b.value()) == args[0]),
+        _ => false,
+    }
+}
@@ -0,0 +1,537 @@
+use super::builtin;
+use super::dsl::SyntaxError;
+use crate::bdi::{Belief, Value};
+use crate::CognitionError;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A first-order term
+///
+/// Atoms start with a lowercase letter, are numbers, or are single-quoted.
+/// Variables start with an uppercase letter or `_`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Term {
+    /// Constant symbol or number
+    Atom(String),
+    /// Logic variable
+    Var(String),
+    /// Functor applied to arguments
+    Compound(String, Vec<Term>),
+}
+
+impl Term {
+    /// Create an atom
+    pub fn atom(name: impl Into<String>) -> Self {
+        Term::Atom(name.into())
+    }
+
+    /// Create a variable
+    pub fn var(name: impl Into<String>) -> Self {
+        Term::Var(name.into())
+    }
+
+    /// Create a compound term
+    pub fn compound(functor: impl Into<String>, args: Vec<Term>) -> Self {
+        Term::Compound(functor.into(), args)
+    }
+
+    /// Parse a term from text
+    pub fn parse(text: &str) -> Result<Self, CognitionError> {
+        let mut parser = TermParser::new(text);
+        let term = parser.condition()?;
+        parser.skip_whitespace();
+        if !parser.at_end() {
+            return Err(parser.error("unexpected trailing input").into());
+        }
+        Ok(term)
+    }
+
+    /// Parse a term, treating unparsable text as an opaque atom
+    pub fn parse_lenient(text: &str) -> Self {
+        Self::parse(text).unwrap_or_else(|_| Term::Atom(text.trim().to_string()))
+    }
+
+    /// Functor name and arity (atoms have arity 0)
+    pub fn functor(&self) -> Option<(&str, usize)> {
+        match self {
+            Term::Atom(name) => Some((name, 0)),
+            Term::Compound(name, args) => Some((name, args.len())),
+            Term::Var(_) => None,
+        }
+    }
+
+    /// Get compound arguments (empty for atoms and variables)
+    pub fn args(&self) -> &[Term] {
+        match self {
+            Term::Compound(_, args) => args,
+            _ => &[],
+        }
+    }
+
+    /// Check if the term is a variable
+    pub fn is_var(&self) -> bool {
+        matches!(self, Term::Var(_))
+    }
+
+    /// Check if the term contains no variables
+    pub fn is_ground(&self) -> bool {
+        match self {
+            Term::Atom(_) => true,
+            Term::Var(_) => false,
+            Term::Compound(_, args) => args.iter().all(Term::is_ground),
+        }
+    }
+
+    /// Variables in order of first occurrence
+    pub fn variables(&self) -> Vec<&str> {
+        let mut vars = Vec::new();
+        self.collect_variables(&mut vars);
+        vars
+    }
+
+    fn collect_variables<'a>(&'a self, vars: &mut Vec<&'a str>) {
+        match self {
+            Term::Var(name) => {
+                if !vars.contains(&name.as_str()) {
+                    vars.push(name);
+                }
+            }
+            Term::Compound(_, args) => args.iter().for_each(|a| a.collect_variables(vars)),
+            Term::Atom(_) => {}
+        }
+    }
+
+    /// Check if a variable occurs in this term
+    pub fn occurs(&self, var: &str) -> bool {
+        match self {
+            Term::Var(name) => name == var,
+            Term::Compound(_, args) => args.iter().any(|a| a.occurs(var)),
+            Term::Atom(_) => false,
+        }
+    }
+
+    /// Numeric value of a number atom
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Term::Atom(name) if is_number(name) => name.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Belief> for Term {
+    /// A belief `key = value` as the fact `key(value)`
+    fn from(belief: &Belief) -> Self {
+        Term::compound(belief.key(), vec![Term::from(belief.value())])
+    }
+}
+
+impl From<&Value> for Term {
+    /// A belief value as an atom, so numbers work in comparisons
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(s) | Value::Symbol(s) => Term::atom(s.as_str()),
+            other => Term::atom(other.to_string()),
+        }
+    }
+}
+
+impl From<&Term> for Value {
+    /// Numbers and `true`/`false` become typed values, other atoms
+    /// symbols and compound terms their text
+    fn from(term: &Term) -> Self {
+        match term {
+            Term::Atom(name) if is_number(name) => match name.parse() {
+                Ok(n) => Value::Integer(n),
+                Err(_) => term
+                    .as_number()
+                    .map_or(Value::symbol(name.as_str()), Value::Float),
+            },
+            Term::Atom(name) => match name.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::symbol(name.as_str()),
+            },
+            other => Value::String(other.to_string()),
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = CognitionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Term::parse(s)
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Term::Atom(name) => write_atom(name, f),
+            Term::Var(name) => write!(f, "{}", name),
+            Term::Compound(op, args) if builtin::is_builtin(self) => {
+                write!(f, "{} {} {}", args[0], op, args[1])
+            }
+            Term::Compound(functor, args) => {
+                write_atom(functor, f)?;
+                write!(f, "(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn write_atom(name: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    if is_plain_atom(name) || is_number(name) {
+        write!(f, "{}", name)
+    } else {
+        write!(f, "'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+fn is_plain_atom(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_number(name: &str) -> bool {
+    let digits = name.strip_prefix('-').unwrap_or(name);
+    let mut parts = digits.splitn(2, '.');
+    let whole = parts.next().unwrap_or("");
+    let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    valid(whole) && parts.next().is_none_or(valid)
+}
+
+/// Variable bindings produced by unification
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Substitution {
+    bindings: HashMap<String, Term>,
+}
+
+impl Substitution {
+    /// Create an empty substitution
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the direct binding of a variable
+    pub fn get(&self, var: &str) -> Option<&Term> {
+        self.bindings.get(var)
+    }
+
+    /// Fully resolved value of a variable, if bound
+    pub fn resolve(&self, var: &str) -> Option<Term> {
+        self.bindings.get(var).map(|t| self.apply(t))
+    }
+
+    /// Bind a variable (no occurs check)
+    pub fn bind(&mut self, var: impl Into<String>, term: Term) {
+        self.bindings.insert(var.into(), term);
+    }
+
+    /// Apply the substitution to a term
+    pub fn apply(&self, term: &Term) -> Term {
+        match term {
+            Term::Var(name) => match self.bindings.get(name) {
+                Some(bound) => self.apply(bound),
+                None => term.clone(),
+            },
+            Term::Compound(functor, args) => Term::Compound(
+                functor.clone(),
+                args.iter().map(|a| self.apply(a)).collect(),
+            ),
+            Term::Atom(_) => term.clone(),
+        }
+    }
+
+    /// Bound variable names
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(String::as_str)
+    }
+
+    /// Number of bindings
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Follow variable bindings until an unbound variable or non-variable
+    fn walk<'a>(&'a self, term: &'a Term) -> &'a Term {
+        let mut current = term;
+        while let Term::Var(name) = current {
+            match self.bindings.get(name) {
+                Some(bound) => current = bound,
+                None => break,
+            }
+        }
+        current
+    }
+
+    fn occurs(&self, var: &str, term: &Term) -> bool {
+        match self.walk(term) {
+            Term::Var(name) => name == var,
+            Term::Compound(_, args) => args.iter().any(|a| self.occurs(var, a)),
+            Term::Atom(_) => false,
+        }
+    }
+
+    /// Walk a term without borrowing the substitution
+    fn resolved<'t>(&self, term: &'t Term) -> Cow<'t, Term> {
+        match term {
+            Term::Var(_) => Cow::Owned(self.walk(term).clone()),
+            _ => Cow::Borrowed(term),
+        }
+    }
+
+    fn unify_in_place(&mut self, a: &Term, b: &Term) -> bool {
+        let a = self.resolved(a);
+        let b = self.resolved(b);
+        match (a.as_ref(), b.as_ref()) {
+            (Term::Var(x), Term::Var(y)) if x == y => true,
+            (Term::Var(x), other) | (other, Term::Var(x)) => {
+                if self.occurs(x, other) {
+                    return false;
+                }
+                self.bindings.insert(x.clone(), other.clone());
+                true
+            }
+            (Term::Atom(x), Term::Atom(y)) => x == y,
+            (Term::Compound(f, xs), Term::Compound(g, ys)) => {
+                f == g
+                    && xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| self.unify_in_place(x, y))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Unify two terms under existing bindings, with occurs check
+///
+/// Returns the extended substitution, or `None` if the terms do not unify.
+pub fn unify(a: &Term, b: &Term, subst: &Substitution) -> Option<Substitution> {
+    let mut extended = subst.clone();
+    extended.unify_in_place(a, b).then_some(extended)
+}
+
+/// Recursive-descent parser over term syntax
+pub(crate) struct TermParser<'a> {
+    text: &'a str,
+    pos: usize,
+    anonymous: usize,
+    comments: bool,
+}
+
+impl<'a> TermParser<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            anonymous: 0,
+            comments: false,
+        }
+    }
+
+    /// Parser that also skips `%` and `//` line comments
+    pub(crate) fn with_comments(text: &'a str) -> Self {
+        Self {
+            comments: true,
+            ..Self::new(text)
+        }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn at_end(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+            let rest = &self.text[self.pos..];
+            if !(self.comments && (rest.starts_with('%') || rest.starts_with("//"))) {
+                break;
+            }
+            self.take_while(|c| c != '\n');
+        }
+    }
+
+    /// Consume `keyword` if it comes next as a whole word
+    pub(crate) fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let whole = rest
+            .strip_prefix(keyword)
+            .is_some_and(|after| !after.starts_with(is_ident_char));
+        if whole {
+            self.pos += keyword.len();
+        }
+        whole
+    }
+
+    /// Consume `symbol` if it comes next
+    pub(crate) fn keyword_symbol(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.text[self.pos..].starts_with(symbol);
+        if found {
+            self.pos += symbol.len();
+        }
+        found
+    }
+
+    pub(crate) fn error(&self, message: &str) -> SyntaxError {
+        self.error_at(self.pos, message)
+    }
+
+    pub(crate) fn error_at(&self, offset: usize, message: &str) -> SyntaxError {
+        SyntaxError::at(self.text, offset, message)
+    }
+
+    pub(crate) fn expect(&mut self, expected: char) -> Result<(), SyntaxError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    pub(crate) fn take_while<F: Fn(char) -> bool>(&mut self, keep: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&keep) {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    /// A term, optionally compared with another: `X > 25`
+    pub(crate) fn condition(&mut self) -> Result<Term, SyntaxError> {
+        let left = self.term()?;
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let Some(op) = builtin::Comparison::ALL
+            .into_iter()
+            .find(|c| rest.starts_with(c.symbol()))
+        else {
+            return Ok(left);
+        };
+        self.pos += op.symbol().len();
+        let right = self.term()?;
+        Ok(Term::Compound(op.symbol().to_string(), vec![left, right]))
+    }
+
+    pub(crate) fn term(&mut self) -> Result<Term, SyntaxError> {
+        self.skip_whitespace();
+        let c = self.peek().ok_or_else(|| self.error("expected a term"))?;
+
+        if c == '_' || c.is_ascii_uppercase() {
+            let name = self.take_while(is_ident_char);
+            if name == "_" {
+                // Every anonymous variable is distinct
+                self.anonymous += 1;
+                return Ok(Term::Var(format!("_G{}", self.anonymous)));
+            }
+            return Ok(Term::Var(name.to_string()));
+        }
+
+        let name = if c.is_ascii_lowercase() {
+            self.take_while(is_ident_char).to_string()
+        } else if c.is_ascii_digit() || c == '-' {
+            self.number()?
+        } else if c == '\'' {
+            self.quoted()?
+        } else {
+            return Err(self.error(&format!("unexpected character '{}'", c)));
+        };
+
+        if self.peek() != Some('(') {
+            return Ok(Term::Atom(name));
+        }
+        self.bump();
+        let mut args = vec![self.term()?];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                    args.push(self.term()?);
+                }
+                _ => break,
+            }
+        }
+        self.expect(')')?;
+        Ok(Term::Compound(name, args))
+    }
+
+    fn number(&mut self) -> Result<String, SyntaxError> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.bump();
+        }
+        self.take_while(|c| c.is_ascii_digit());
+        let mut fraction = self.text[self.pos..].chars();
+        if fraction.next() == Some('.') && fraction.next().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            self.take_while(|c| c.is_ascii_digit());
+        }
+        let text = &self.text[start..self.pos];
+        if is_number(text) {
+            Ok(text.to_string())
+        } else {
+            self.pos = start;
+            Err(self.error("malformed number"))
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, SyntaxError> {
+        self.bump();
+        let mut name = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(name),
+                Some('\\') => match self.bump() {
+                    Some(c) => name.push(c),
+                    None => break,
+                },
+                Some(c) => name.push(c),
+                None => break,
+            }
+        }
+        Err(self.error("unterminated quoted atom"))
+    }
+}
+
+pub(crate) fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
@@ -0,0 +1,232 @@
+use super::certainty::{combine_evidence, CertaintyCombination};
+use super::Term;
+use std::collections::{HashMap, HashSet};
+
+/// Why a derived fact holds: a rule fired on the given facts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Justification {
+    rule: String,
+    strength: f64,
+    antecedents: Vec<Term>,
+}
+
+impl Justification {
+    /// Get the rule name
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// Get the strength of the rule
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// Get the facts the rule matched
+    pub fn antecedents(&self) -> &[Term] {
+        &self.antecedents
+    }
+}
+
+/// Justification-based truth maintenance
+///
+/// Premises are facts asserted from outside, each with a certainty; every
+/// other fact is believed only while one of its justifications has all
+/// antecedents believed.
+/// Justifications are kept after their conclusion is retracted so it can be
+/// restored when support returns.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TruthMaintenance {
+    premises: HashMap<Term, f64>,
+    justifications: HashMap<Term, Vec<Justification>>,
+    /// Conclusions justified by each antecedent
+    consequences: HashMap<Term, Vec<Term>>,
+}
+
+impl TruthMaintenance {
+    pub(crate) fn add_premise(&mut self, fact: Term, certainty: f64) {
+        self.premises.insert(fact, certainty);
+    }
+
+    pub(crate) fn remove_premise(&mut self, fact: &Term) {
+        self.premises.remove(fact);
+    }
+
+    pub(crate) fn is_premise(&self, fact: &Term) -> bool {
+        self.premises.contains_key(fact)
+    }
+
+    /// Record that `rule` derived `conclusion` from `antecedents`
+    pub(crate) fn justify(
+        &mut self,
+        conclusion: &Term,
+        rule: &str,
+        strength: f64,
+        antecedents: Vec<Term>,
+    ) {
+        let justification = Justification {
+            rule: rule.to_string(),
+            strength,
+            antecedents,
+        };
+        let recorded = self.justifications.entry(conclusion.clone()).or_default();
+        if recorded.contains(&justification) {
+            return;
+        }
+        for antecedent in &justification.antecedents {
+            let dependents = self.consequences.entry(antecedent.clone()).or_default();
+            if !dependents.contains(conclusion) {
+                dependents.push(conclusion.clone());
+            }
+        }
+        recorded.push(justification);
+    }
+
+    /// Justifications of a fact whose antecedents all satisfy `believed`
+    pub(crate) fn valid<'a>(
+        &'a self,
+        fact: &Term,
+        believed: impl Fn(&Term) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a Justification> + 'a {
+        self.justifications
+            .get(fact)
+            .into_iter()
+            .flatten()
+            .filter(move |j| j.antecedents.iter().all(&believed))
+    }
+
+    /// Conclusions that become supported once `fact` is believed
+    pub(crate) fn restorable(&self, fact: &Term, believed: impl Fn(&Term) -> bool) -> Vec<Term> {
+        self.consequences
+            .get(fact)
+            .into_iter()
+            .flatten()
+            .filter(|c| !believed(c) && self.valid(c, &believed).next().is_some())
+            .cloned()
+            .collect()
+    }
+
+    /// Every conclusion that depends on `fact`, directly or not
+    pub(crate) fn dependents(&self, fact: &Term) -> Vec<Term> {
+        let mut seen = HashSet::from([fact]);
+        let mut dependents = Vec::new();
+        let mut stack = vec![fact];
+        while let Some(current) = stack.pop() {
+            for dependent in self.consequences.get(current).into_iter().flatten() {
+                if seen.insert(dependent) {
+                    dependents.push(dependent.clone());
+                    stack.push(dependent);
+                }
+            }
+        }
+        dependents
+    }
+
+    /// `fact` and its believed dependents that have no well-founded support
+    ///
+    /// The fact and its dependents are first assumed out, then support is
+    /// rebuilt from facts outside that set, so cyclic justifications cannot
+    /// keep each other alive.
+    pub(crate) fn unsupported(&self, fact: &Term, believed: impl Fn(&Term) -> bool) -> Vec<Term> {
+        let mut affected = vec![fact.clone()];
+        let mut seen = HashSet::from([fact.clone()]);
+        let mut stack = vec![fact];
+        while let Some(current) = stack.pop() {
+            for dependent in self.consequences.get(current).into_iter().flatten() {
+                if believed(dependent) && seen.insert(dependent.clone()) {
+                    affected.push(dependent.clone());
+                    stack.push(dependent);
+                }
+            }
+        }
+
+        let mut supported: HashSet<&Term> = HashSet::new();
+        loop {
+            let before = supported.len();
+            for node in &affected {
+                if supported.contains(node) {
+                    continue;
+                }
+                let holds = |t: &Term| supported.contains(t) || (believed(t) && !seen.contains(t));
+                let justified =
+                    self.premises.contains_key(node) || self.valid(node, holds).next().is_some();
+                if justified {
+                    supported.insert(node);
+                }
+            }
+            if supported.len() == before {
+                break;
+            }
+        }
+        affected
+            .iter()
+            .filter(|n| !supported.contains(n))
+            .cloned()
+            .collect()
+    }
+
+    /// Certainty of a believed fact
+    ///
+    /// Premises have their asserted certainty. A justification contributes
+    /// its rule strength times the combined certainty of its antecedents, and
+    /// several justifications combine as independent evidence. Justifications
+    /// that depend on the fact itself are ignored.
+    pub(crate) fn certainty(
+        &self,
+        fact: &Term,
+        combination: CertaintyCombination,
+        believed: &impl Fn(&Term) -> bool,
+    ) -> f64 {
+        let mut memo = HashMap::new();
+        self.certainty_within(fact, combination, believed, &mut Vec::new(), &mut memo)
+            .0
+    }
+
+    /// Certainty of `fact` and whether a cycle was cut below it
+    ///
+    /// Only results that cut no cycle are memoised: they do not depend on
+    /// the path that reached them, so shared antecedents are computed once.
+    fn certainty_within<'a>(
+        &'a self,
+        fact: &'a Term,
+        combination: CertaintyCombination,
+        believed: &impl Fn(&Term) -> bool,
+        visiting: &mut Vec<&'a Term>,
+        memo: &mut HashMap<&'a Term, f64>,
+    ) -> (f64, bool) {
+        if let Some(&certainty) = self.premises.get(fact) {
+            return (certainty, false);
+        }
+        if let Some(&certainty) = memo.get(fact) {
+            return (certainty, false);
+        }
+        if visiting.contains(&fact) {
+            return (0.0, true);
+        }
+        visiting.push(fact);
+        let mut total = 0.0;
+        let mut cut = false;
+        let justifications = self.justifications.get(fact).into_iter().flatten();
+        for justification in justifications.filter(|j| j.antecedents.iter().all(believed)) {
+            let mut conditions = Vec::with_capacity(justification.antecedents.len());
+            for antecedent in &justification.antecedents {
+                let (certainty, cycle) =
+                    self.certainty_within(antecedent, combination, believed, visiting, memo);
+                conditions.push(certainty);
+                cut |= cycle;
+            }
+            let support = justification.strength * combination.conjunction(conditions);
+            total = combine_evidence(total, support);
+        }
+        visiting.pop();
+        if !cut {
+            memo.insert(fact, total);
+        }
+        (total, cut)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.premises.clear();
+        self.justifications.clear();
+        self.consequences.clear();
+    }
+}
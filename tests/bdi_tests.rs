@@ -66,3 +66,324 @@ fn bdi_agent_creation() {
     assert!(agent.desires().is_empty());
     assert!(agent.intentions().is_empty());
 }
+
+#[test]
+fn intention_guarded_by_temporal_property() {
+    use agentropic_cognition::reasoning::Monitor;
+
+    let plan = Plan::new("deliver")
+        .add_action(Action::new("pick_up"))
+        .add_action(Action::new("fly"))
+        .add_action(Action::new("drop"));
+    let guard = Monitor::new("always !(carrying && battery < 10)").unwrap();
+    let mut intention = Intention::new(Goal::achievement("delivered"), plan).with_guard(guard);
+
+    let mut beliefs = BeliefBase::new();
+    beliefs.add(Belief::new("battery", "40"));
+    intention.advance(&beliefs).unwrap();
+    beliefs.add(Belief::new("carrying", "true"));
+    beliefs.add(Belief::new("battery", "5"));
+    let error = intention.advance(&beliefs).unwrap_err();
+    assert!(matches!(error, CognitionError::PropertyViolated(ref v) if v.step() == 1));
+    assert_eq!(intention.current_step(), 1);
+
+    intention.reset();
+    assert!(intention.guards()[0].violation().is_none());
+
+    // A guard that is false outright blocks the first step
+    let guard = Monitor::new("false").unwrap();
+    assert_eq!(guard.violation().map(|v| v.step()), Some(0));
+    let plan = Plan::new("idle").add_action(Action::new("wait"));
+    let mut intention = Intention::new(Goal::achievement("done"), plan).with_guard(guard);
+    let error = intention.advance(&beliefs).unwrap_err();
+    assert!(matches!(error, CognitionError::PropertyViolated(ref v) if v.step() == 0));
+    assert_eq!(intention.current_step(), 0);
+}
+
+#[test]
+fn typed_belief_values() {
+    use agentropic_cognition::Value;
+    use std::collections::BTreeMap;
+
+    let mut base = BeliefBase::new();
+    base.add(Belief::new("battery", 80));
+    base.add(Belief::new("temperature", 21.5));
+    base.add(Belief::new("charging", false));
+    base.add(Belief::new("status", Value::symbol("idle")));
+    base.add(Belief::new("legacy", "42"));
+    base.add(Belief::new(
+        "route",
+        vec![Value::symbol("a"), Value::symbol("b")],
+    ));
+    base.add(Belief::new(
+        "pose",
+        BTreeMap::from([
+            ("x".to_string(), Value::from(3)),
+            ("y".to_string(), Value::from(4)),
+        ]),
+    ));
+
+    assert_eq!(base.get_f64("battery"), Some(80.0));
+    assert_eq!(base.get_i64("battery"), Some(80));
+    assert_eq!(base.get_bool("charging"), Some(false));
+    assert_eq!(base.get_str("status"), Some("idle"));
+    assert_eq!(base.get_f64("status"), None);
+    assert_eq!(base.get_f64("missing"), None);
+
+    // Numeric text is stored as a number
+    assert_eq!(base.value("legacy").unwrap(), &Value::Integer(42));
+    assert_eq!(base.get_i64("legacy"), Some(42));
+    assert_eq!(base.value("legacy").unwrap(), "42");
+    assert_eq!(base.value("battery").unwrap(), "80");
+    assert_eq!(base.value("route").unwrap().to_string(), "[a, b]");
+    assert_eq!(base.value("pose").unwrap().to_string(), "{x: 3, y: 4}");
+
+    // Integers and floats compare numerically
+    assert!(base.value("battery").unwrap() > 50);
+    assert!(base.value("temperature").unwrap() < 22.0);
+    assert_eq!(Value::from(80), Value::from(80.0));
+    assert_eq!(base.value("charging").unwrap(), &Value::from("false"));
+    assert_ne!(
+        base.value("charging").unwrap(),
+        &Value::String("false".into())
+    );
+    assert_eq!(Value::symbol("a").partial_cmp(&Value::from(1)), None);
+
+    // Old-style string beliefs compare as the numbers they spell
+    let battery = Belief::new("battery", "80");
+    assert!(battery.value() > 50);
+    assert_eq!(battery.value(), 80);
+    assert_eq!(Value::from("80"), Value::from(80));
+    assert_eq!(Value::from(80), Value::from("80"));
+    assert_eq!(Value::from("21.5"), Value::from(21.5));
+    assert_eq!(Belief::new("x", "1.50").value(), "1.50");
+    assert_eq!(Belief::new("x", "10.0").value(), "10.0");
+    assert_eq!(Value::from("007").as_str(), Some("007"));
+    assert_eq!(Value::from("1e3").as_str(), Some("1e3"));
+    let mut warm: Vec<&str> = base
+        .query(|b| b.value() >= 21.0)
+        .iter()
+        .map(|b| b.key())
+        .collect();
+    warm.sort();
+    assert_eq!(warm, vec!["battery", "legacy", "temperature"]);
+
+    // Typed beliefs reach the reasoning engine as comparable facts
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("low")
+            .with_condition("battery(X)")
+            .with_condition("X < 90")
+            .with_conclusion("recharge(soon)"),
+    );
+    engine.sync_beliefs(&base);
+    engine.infer();
+    assert!(engine.has_fact("recharge(soon)"));
+    let derived = engine.derived_beliefs();
+    assert_eq!(derived[0].value(), &Value::symbol("soon"));
+}
+
+fn weather_beliefs() -> BeliefBase {
+    use agentropic_cognition::bdi::Constraint;
+
+    BeliefBase::new()
+        .with_constraint(
+            Constraint::new("weather")
+                .with_key("raining")
+                .with_key("sunny"),
+        )
+        .with_constraint(
+            Constraint::new("door")
+                .with_value("door", "open")
+                .with_value("locked", true),
+        )
+}
+
+#[test]
+fn belief_revision_retracts_conflicting_beliefs() {
+    let mut base = weather_beliefs();
+    base.add(Belief::new("raining", true));
+    base.add(Belief::new("door", "open"));
+    base.add(Belief::new("temperature", 18));
+    assert!(base.is_consistent());
+
+    let report = base.revise(Belief::new("sunny", true)).unwrap();
+    assert!(report.was_retracted("raining"));
+    assert_eq!(report.violated(), ["weather"]);
+    assert!(base.contains("sunny") && !base.contains("raining"));
+
+    // Locking conflicts with the open door, unlocking does not
+    let report = base.revise(Belief::new("locked", false)).unwrap();
+    assert!(report.is_empty());
+    let report = base.revise(Belief::new("locked", true)).unwrap();
+    assert_eq!(report.retracted().len(), 2);
+    assert!(report.was_retracted("door") && report.was_retracted("locked"));
+    assert_eq!(report.violated(), ["door"]);
+    assert_eq!(base.get_bool("locked"), Some(true));
+    assert!(base.contains("temperature"));
+
+    // Contraction reports what was given up
+    assert!(base.contract("temperature").was_retracted("temperature"));
+    assert!(base.contract("temperature").is_empty());
+
+    // Expansion skips the checks
+    base.expand(Belief::new("raining", true));
+    assert_eq!(base.violations()[0].name(), "weather");
+}
+
+#[test]
+fn belief_revision_consolidates_by_entrenchment() {
+    use agentropic_cognition::bdi::{Constraint, Entrenchment};
+
+    // Less certain beliefs give way first
+    let mut base = weather_beliefs();
+    base.expand(Belief::with_certainty("raining", true, 0.9));
+    base.expand(Belief::with_certainty("sunny", true, 0.4));
+    let report = base.consolidate();
+    assert_eq!(report.retracted()[0].key(), "sunny");
+    assert!(base.is_consistent());
+
+    // Under recency the older belief gives way
+    let mut base = weather_beliefs().with_entrenchment(Entrenchment::Recency);
+    base.expand(Belief::with_certainty("raining", true, 0.9));
+    base.expand(Belief::with_certainty("sunny", true, 0.4));
+    assert!(base.consolidate().was_retracted("raining"));
+
+    // New constraints restore consistency when added
+    let mut base = BeliefBase::new();
+    base.add(Belief::new("mode", "manual"));
+    base.add(Belief::with_certainty("autopilot", true, 0.5));
+    let report = base.add_constraint(
+        Constraint::new("control")
+            .with_value("mode", "manual")
+            .with_key("autopilot"),
+    );
+    assert!(report.was_retracted("autopilot"));
+
+    // A belief meeting two conditions of one constraint cannot be accepted
+    let mut base = BeliefBase::new().with_constraint(
+        Constraint::new("odd")
+            .with_key("door")
+            .with_value("door", "open"),
+    );
+    assert!(matches!(
+        base.revise(Belief::new("door", "open")),
+        Err(CognitionError::BeliefRevisionFailed(_))
+    ));
+    assert!(base.is_empty());
+
+    // Typed conditions match beliefs given as text
+    let mut base = BeliefBase::new().with_constraint(
+        Constraint::new("power")
+            .with_value("battery", 80)
+            .with_key("charging"),
+    );
+    base.add(Belief::new("charging", "true"));
+    let report = base.revise(Belief::new("battery", "80")).unwrap();
+    assert!(report.was_retracted("charging"));
+}
+
+#[test]
+fn beliefs_stored_before_typed_values_still_load() {
+    use agentropic_cognition::Value;
+
+    let old = r#"{"key":"battery","value":"80","certainty":0.9}"#;
+    let belief: Belief = serde_json::from_str(old).unwrap();
+    assert_eq!(belief.value(), &Value::Integer(80));
+    assert!(belief.value() > 50);
+    let old = r#"{"key":"mode","value":"manual","certainty":1.0}"#;
+    let belief: Belief = serde_json::from_str(old).unwrap();
+    assert_eq!(belief.as_str(), Some("manual"));
+
+    // Typed values round-trip, including symbols and nested values
+    let route = Belief::new(
+        "route",
+        vec![Value::symbol("a"), Value::from("b"), 3.into()],
+    );
+    let json = serde_json::to_string(&route).unwrap();
+    let loaded: Belief = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, route);
+    assert_eq!(loaded.value().as_list().unwrap()[0], Value::symbol("a"));
+}
+
+#[test]
+fn belief_provenance_and_source_reliability() {
+    use agentropic_cognition::bdi::Source;
+    use std::time::{Duration, SystemTime};
+
+    let lidar = Source::Sensor("lidar".to_string());
+    let scout = Source::Agent("scout".to_string());
+    let mut base = BeliefBase::new().with_reliability(scout.clone(), 0.5);
+
+    let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    base.add(
+        Belief::new("obstacle", true)
+            .with_source(lidar.clone())
+            .with_evidence("scan 1042")
+            .with_timestamp(an_hour_ago),
+    );
+    base.add(Belief::with_certainty("enemy_near", true, 0.8).with_source(scout.clone()));
+    base.add(Belief::new("mission", "survey").with_source(Source::User("ops".to_string())));
+
+    // Certainty is weighted by the source's reliability
+    assert!((base.get("enemy_near").unwrap().certainty() - 0.4).abs() < 1e-9);
+    assert_eq!(base.get("obstacle").unwrap().certainty(), 1.0);
+    assert_eq!(base.reliability(&Source::Unknown), 1.0);
+
+    // Re-adding or revising with a held belief does not weight it again
+    let held = base.get("enemy_near").unwrap().clone();
+    base.add(held.clone());
+    base.add(held.clone());
+    base.revise(held).unwrap();
+    assert!((base.get("enemy_near").unwrap().certainty() - 0.4).abs() < 1e-9);
+    base.set_reliability(scout.clone(), 1.0);
+    assert!((base.get("enemy_near").unwrap().certainty() - 0.8).abs() < 1e-9);
+    base.set_reliability(scout.clone(), 0.5);
+
+    // The reported certainty is kept apart from the weighted one
+    let mut held = base.get("enemy_near").unwrap().clone();
+    held.set_certainty(0.6);
+    assert_eq!(held.reported_certainty(), 0.6);
+    assert!((held.certainty() - 0.3).abs() < 1e-9);
+
+    // Beliefs formed at different times are still equal
+    let earlier = Belief::new("x", "1").with_timestamp(an_hour_ago);
+    assert_eq!(earlier, Belief::new("x", "1"));
+    assert_ne!(earlier, Belief::new("x", "2"));
+
+    let obstacle = base.get("obstacle").unwrap();
+    assert_eq!(obstacle.source(), &lidar);
+    assert_eq!(obstacle.source().to_string(), "sensor:lidar");
+    assert_eq!(obstacle.evidence(), ["scan 1042"]);
+    assert!(obstacle.age() >= Duration::from_secs(3600));
+
+    assert_eq!(base.from_source(&scout)[0].key(), "enemy_near");
+    assert!(base.from_source(&Source::Unknown).is_empty());
+    let stale: Vec<&str> = base
+        .older_than(Duration::from_secs(60))
+        .iter()
+        .map(|b| b.key())
+        .collect();
+    assert_eq!(stale, vec!["obstacle"]);
+    assert_eq!(base.newer_than(Duration::from_secs(60)).len(), 2);
+
+    // Confirming a belief refreshes it but keeps when it was first formed
+    base.add(Belief::new("obstacle", true).with_source(lidar));
+    let obstacle = base.get("obstacle").unwrap();
+    assert_eq!(obstacle.created_at(), an_hour_ago);
+    assert!(obstacle.age() < Duration::from_secs(60));
+    assert!(base.older_than(Duration::from_secs(60)).is_empty());
+
+    // Derived beliefs record the rule and the facts it used
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("halt")
+            .with_condition("obstacle(true)")
+            .with_conclusion("action(stop)"),
+    );
+    engine.sync_beliefs(&base);
+    engine.infer();
+    let derived = engine.derived_beliefs();
+    assert_eq!(derived[0].source(), &Source::Inference("halt".to_string()));
+    assert_eq!(derived[0].evidence(), ["obstacle(true)"]);
+}
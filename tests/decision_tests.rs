@@ -0,0 +1,227 @@
+use agentropic_cognition::decision::voting::VoteOutcome;
+use agentropic_cognition::decision::{DecisionNode, DecisionTree, Parameter};
+use agentropic_cognition::prelude::*;
+use agentropic_cognition::Reason;
+
+fn agent_with(goals: &[(&str, f64)]) -> BDIAgent {
+    let mut agent = BDIAgent::new();
+    for (name, priority) in goals {
+        agent.add_desire(Desire::new(Goal::achievement(*name), *priority));
+    }
+    agent
+}
+
+fn ballots(election: Election, rankings: &[(&[&str], usize)]) -> Election {
+    rankings
+        .iter()
+        .fold(election, |election, (ranking, count)| {
+            (0..*count).fold(election, |e, _| {
+                e.with_ballot(Ballot::new(ranking.iter().copied()))
+            })
+        })
+}
+
+fn run(election: &Election, rule: VotingRule) -> VoteOutcome {
+    election.run(rule).unwrap()
+}
+
+#[test]
+fn ballot_from_desires_ranks_by_priority() {
+    let agent = agent_with(&[("explore", 0.3), ("recharge", 0.9), ("deliver", 0.6)]);
+    let ballot = Ballot::from_agent(&agent);
+
+    assert_eq!(ballot.ranking(), ["recharge", "deliver", "explore"]);
+}
+
+#[test]
+fn rules_can_disagree() {
+    // Classic profile where plurality, IRV and Borda/Condorcet pick differently
+    let election = ballots(
+        Election::new(["a", "b", "c"]),
+        &[
+            (&["a", "c", "b"], 4),
+            (&["b", "c", "a"], 3),
+            (&["c", "b", "a"], 2),
+        ],
+    );
+
+    assert_eq!(run(&election, VotingRule::Plurality).winner(), "a");
+    assert_eq!(run(&election, VotingRule::InstantRunoff).winner(), "b");
+    assert_eq!(run(&election, VotingRule::Borda).winner(), "c");
+    assert_eq!(run(&election, VotingRule::Copeland).winner(), "c");
+    assert_eq!(run(&election, VotingRule::Kemeny).winner(), "c");
+    assert_eq!(election.condorcet_winner(), Some("c"));
+}
+
+#[test]
+fn approval_counts_top_k() {
+    let election = ballots(
+        Election::new(["a", "b", "c"]),
+        &[(&["a", "b", "c"], 2), (&["c", "b", "a"], 2)],
+    );
+
+    let outcome = run(&election, VotingRule::Approval(2));
+    assert_eq!(outcome.winner(), "b");
+    assert_eq!(outcome.scores()["b"], 4.0);
+}
+
+#[test]
+fn condorcet_cycle_is_detected() {
+    let election = ballots(
+        Election::new(["rock", "paper", "scissors", "lizard"]),
+        &[
+            (&["rock", "scissors", "paper", "lizard"], 1),
+            (&["paper", "rock", "scissors", "lizard"], 1),
+            (&["scissors", "paper", "rock", "lizard"], 1),
+        ],
+    );
+
+    assert_eq!(election.condorcet_winner(), None);
+    assert_eq!(
+        election.condorcet_cycles(),
+        vec![vec!["rock", "paper", "scissors"]]
+    );
+}
+
+#[test]
+fn tie_break_configuration() {
+    let election = ballots(
+        Election::new(["beta", "alpha"]),
+        &[(&["beta", "alpha"], 1), (&["alpha", "beta"], 1)],
+    );
+
+    assert_eq!(run(&election, VotingRule::Plurality).winner(), "beta");
+
+    let lexicographic = election.clone().with_tie_break(TieBreak::Lexicographic);
+    assert_eq!(run(&lexicographic, VotingRule::Borda).winner(), "alpha");
+
+    let strict = election.with_tie_break(TieBreak::Fail);
+    assert!(matches!(
+        strict.run(VotingRule::InstantRunoff),
+        Err(CognitionError::DecisionFailed(_))
+    ));
+}
+
+#[test]
+fn election_from_agents() {
+    let agents = vec![
+        agent_with(&[("patrol", 0.8), ("charge", 0.4)]),
+        agent_with(&[("charge", 0.9), ("patrol", 0.2)]),
+        agent_with(&[("patrol", 0.7), ("charge", 0.6)]),
+    ];
+    let election = Election::from_agents(&agents);
+
+    assert_eq!(election.candidates(), ["patrol", "charge"]);
+    assert_eq!(run(&election, VotingRule::Plurality).winner(), "patrol");
+}
+
+#[test]
+fn kemeny_rejects_large_candidate_sets() {
+    let election = Election::new((0..9).map(|i| format!("goal{}", i)));
+    assert!(election.run(VotingRule::Kemeny).is_err());
+}
+
+fn drilling_tree() -> DecisionTree {
+    DecisionTree::new(
+        DecisionNode::decision("drill?")
+            .with_option(
+                "drill",
+                DecisionNode::chance("oil")
+                    .with_outcome("wet", 0.3, DecisionNode::terminal("gusher", 100.0))
+                    .with_outcome("dry", 0.7, DecisionNode::terminal("dry_hole", -30.0)),
+            )
+            .with_option("skip", DecisionNode::terminal("nothing", 0.0)),
+    )
+}
+
+#[test]
+fn decision_tree_rollback() {
+    let tree = drilling_tree();
+
+    assert!((tree.expected_value().unwrap() - 9.0).abs() < 1e-9);
+    let policy = tree.policy().unwrap();
+    assert_eq!(policy.len(), 1);
+    assert_eq!(policy[0].option(), "drill");
+    assert_eq!(policy[0].alternatives().len(), 2);
+}
+
+#[test]
+fn decision_tree_value_of_perfect_information() {
+    let tree = drilling_tree();
+
+    let evpi = tree.value_of_perfect_information("oil").unwrap();
+    assert!((evpi - 21.0).abs() < 1e-9);
+    assert!(tree.value_of_perfect_information("weather").is_err());
+
+    // Same-named chance nodes must describe the same uncertainty
+    let oil = |wet: f64| {
+        DecisionNode::chance("oil")
+            .with_outcome("wet", wet, DecisionNode::terminal("gusher", 100.0))
+            .with_outcome("dry", 1.0 - wet, DecisionNode::terminal("dry_hole", -30.0))
+    };
+    let tree = DecisionTree::new(
+        DecisionNode::decision("site")
+            .with_option("north", oil(0.3))
+            .with_option("south", oil(0.6)),
+    );
+    assert!(tree.value_of_perfect_information("oil").is_err());
+    let tree = DecisionTree::new(
+        DecisionNode::decision("site")
+            .with_option("north", oil(0.3))
+            .with_option("south", oil(0.3)),
+    );
+    assert!(tree.value_of_perfect_information("oil").unwrap().abs() < 1e-9);
+}
+
+#[test]
+fn decision_tree_sensitivity() {
+    let tree = drilling_tree();
+
+    let points = tree
+        .sensitivity(&Parameter::probability("oil", "wet"), &[0.1, 0.5])
+        .unwrap();
+    assert_eq!(points[0].best_option(), Some("skip"));
+    assert_eq!(points[0].expected_value(), 0.0);
+    assert_eq!(points[1].best_option(), Some("drill"));
+    assert!((points[1].expected_value() - 35.0).abs() < 1e-9);
+
+    let points = tree
+        .sensitivity(&Parameter::payoff("gusher"), &[60.0])
+        .unwrap();
+    assert_eq!(points[0].best_option(), Some("skip"));
+}
+
+#[test]
+fn decision_tree_rejects_bad_probabilities() {
+    let tree = DecisionTree::new(
+        DecisionNode::chance("coin")
+            .with_outcome("heads", 0.5, DecisionNode::terminal("h", 1.0))
+            .with_outcome("tails", 0.6, DecisionNode::terminal("t", 0.0)),
+    );
+
+    assert!(matches!(
+        tree.expected_value(),
+        Err(CognitionError::DecisionFailed(_))
+    ));
+}
+
+#[test]
+fn decision_choice_explanation() {
+    let policy = drilling_tree().policy().unwrap();
+    let explanation = policy[0].explain();
+
+    assert_eq!(explanation.subject(), "drill?: drill");
+    let options: Vec<(&str, bool)> = explanation
+        .children()
+        .iter()
+        .map(|c| (c.subject(), matches!(c.reason(), Reason::Chosen(_))))
+        .collect();
+    assert_eq!(options, vec![("drill", true), ("skip", false)]);
+    assert_eq!(explanation.children()[1].reason(), &Reason::Rejected(0.0));
+    assert!(explanation
+        .to_string()
+        .ends_with("  skip [rejected, utility 0]\n"));
+    assert!(explanation
+        .to_json()
+        .contains(r#"{"subject":"skip","reason":"rejected","utility":0,"children":[]}"#));
+}
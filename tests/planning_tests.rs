@@ -1,4 +1,5 @@
 use agentropic_cognition::prelude::*;
+use agentropic_cognition::Reason;
 
 #[test]
 fn create_action() {
@@ -38,3 +39,52 @@ fn planner_creation() {
     let result = planner.plan(&initial, &goal);
     assert!(result.is_ok());
 }
+
+#[test]
+fn planner_explains_which_goals_each_action_achieves() {
+    let planner = Planner::new();
+    let initial = State::new()
+        .set("at", "office")
+        .set("has_keys", "true")
+        .set("lights", "on");
+    let goal = State::new()
+        .set("at", "home")
+        .set("lights", "off")
+        .set("has_keys", "true");
+    let plan = Plan::new("go_home")
+        .add_action(Action::new("switch_off").with_effect("lights", "off"))
+        .add_action(
+            Action::new("drive")
+                .with_parameter("home")
+                .with_precondition("has_keys", "true")
+                .with_effect("at", "home"),
+        );
+
+    let explanation = planner.explain(&initial, &goal, &plan).unwrap();
+    assert_eq!(explanation.reason(), &Reason::Plan);
+    assert_eq!(
+        explanation.to_string(),
+        "go_home [plan]\n  has_keys=true [fact]\n  switch_off [achieves lights=off]\n  drive(home) [achieves at=home]\n"
+    );
+    assert_eq!(
+        explanation.find("drive(home)").unwrap().to_json(),
+        r#"{"subject":"drive(home)","reason":"action","achieves":["at=home"],"children":[]}"#
+    );
+
+    // Preconditions are checked as the plan is replayed
+    let reversed = Plan::new("broken").add_action(
+        Action::new("drive")
+            .with_precondition("has_keys", "false")
+            .with_effect("at", "home"),
+    );
+    assert!(matches!(
+        planner.explain(&initial, &goal, &reversed),
+        Err(CognitionError::PlanningFailed(_))
+    ));
+    let partial =
+        Plan::new("partial").add_action(Action::new("switch_off").with_effect("lights", "off"));
+    assert!(matches!(
+        planner.explain(&initial, &goal, &partial),
+        Err(CognitionError::GoalNotAchievable(message)) if message == "at=home"
+    ));
+}
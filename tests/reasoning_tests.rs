@@ -0,0 +1,1741 @@
+use agentropic_cognition::prelude::*;
+use agentropic_cognition::reasoning::{
+    combine_evidence, load_rules, parse_rules, unify, Abduction, ArgumentSet,
+    ArgumentationFramework, AttackKind, BayesianNetwork, Case, CaseBase, CertaintyCombination,
+    ConflictStrategy, Csp, DatalogProgram, Defuzzification, Evidence, FuzzyRule, FuzzySystem,
+    LinguisticVariable, LintKind, MatchStrategy, Membership, Monitor, Ontology, Outcome, ProofStep,
+    QueryOptions, Retention, RuleKind, Semantics, Severity, Similarity, Substitution, SumBound,
+    TemporalFormula, Verdict,
+};
+use agentropic_cognition::Reason;
+use std::collections::HashMap;
+
+#[test]
+fn forward_chaining_to_fixpoint() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("uncomfortable")
+            .with_condition("hot")
+            .with_condition("humid")
+            .with_conclusion("uncomfortable"),
+    );
+    engine.add_rule(
+        Rule::new("hot_weather")
+            .with_condition("sunny")
+            .with_conclusion("hot"),
+    );
+    engine.add_fact("sunny");
+    engine.add_fact("humid");
+
+    let derived = engine.infer();
+
+    assert_eq!(derived, vec!["hot", "uncomfortable"]);
+    assert!(engine.has_fact("uncomfortable"));
+    assert!(engine.infer().is_empty());
+}
+
+#[test]
+fn rule_needs_every_condition() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("r")
+            .with_condition("a")
+            .with_condition("b")
+            .with_conclusion("c"),
+    );
+    engine.add_fact("a");
+
+    assert!(engine.infer().is_empty());
+    assert!(!engine.has_fact("c"));
+}
+
+#[test]
+fn cyclic_rules_terminate() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(Rule::new("ab").with_condition("a").with_conclusion("b"));
+    engine.add_rule(Rule::new("ba").with_condition("b").with_conclusion("a"));
+    engine.add_fact("a");
+
+    assert_eq!(engine.infer(), vec!["b"]);
+    assert_eq!(engine.facts().len(), 2);
+}
+
+#[test]
+fn parse_and_display_terms() {
+    let term = Term::parse("likes(X, pair(bob, 'Alice Smith'), 42)").unwrap();
+
+    assert_eq!(term.functor(), Some(("likes", 3)));
+    assert_eq!(term.variables(), vec!["X"]);
+    assert!(!term.is_ground());
+    assert_eq!(term.to_string(), "likes(X,pair(bob,'Alice Smith'),42)");
+    assert!(Term::parse("p(a,").is_err());
+}
+
+#[test]
+fn unification_binds_variables() {
+    let a = Term::parse("p(X, f(Y))").unwrap();
+    let b = Term::parse("p(g(Z), f(a))").unwrap();
+
+    let subst = unify(&a, &b, &Substitution::new()).unwrap();
+    assert_eq!(subst.resolve("Y"), Some(Term::atom("a")));
+    assert_eq!(subst.apply(&a), subst.apply(&b));
+
+    let clash = Term::parse("p(a)").unwrap();
+    assert!(unify(&clash, &Term::parse("p(b)").unwrap(), &Substitution::new()).is_none());
+}
+
+#[test]
+fn unification_occurs_check() {
+    let x = Term::var("X");
+    let fx = Term::parse("f(X)").unwrap();
+
+    assert!(unify(&x, &fx, &Substitution::new()).is_none());
+}
+
+#[test]
+fn rule_variables_propagate_to_conclusions() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("mortality_rule")
+            .with_condition("human(X)")
+            .with_conclusion("mortal(X)"),
+    );
+    engine.add_rule(
+        Rule::new("grandparent")
+            .with_condition("parent(X, Y)")
+            .with_condition("parent(Y, Z)")
+            .with_conclusion("grandparent(X, Z)"),
+    );
+    engine.add_fact("human(socrates)");
+    engine.add_fact("human(plato)");
+    engine.add_fact("parent(ann, bob)");
+    engine.add_fact("parent(bob, cid)");
+
+    let derived = engine.infer();
+
+    assert_eq!(
+        derived,
+        vec!["mortal(socrates)", "mortal(plato)", "grandparent(ann,cid)"]
+    );
+    assert!(engine.has_fact("grandparent(ann, cid)"));
+    assert_eq!(
+        engine
+            .facts_matching(&Term::parse("mortal(Who)").unwrap())
+            .len(),
+        2
+    );
+}
+
+fn family() -> ReasoningEngine {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("ancestor_base")
+            .with_condition("parent(X, Y)")
+            .with_conclusion("ancestor(X, Y)"),
+    );
+    engine.add_rule(
+        Rule::new("ancestor_step")
+            .with_condition("ancestor(X, Z)")
+            .with_condition("parent(Z, Y)")
+            .with_conclusion("ancestor(X, Y)"),
+    );
+    engine.add_fact("parent(ann, bob)");
+    engine.add_fact("parent(bob, cid)");
+    engine.add_fact("parent(cid, dee)");
+    engine
+}
+
+#[test]
+fn backward_chaining_returns_bindings_and_proof() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("mortality_rule")
+            .with_condition("human(X)")
+            .with_conclusion("mortal(X)"),
+    );
+    engine.add_fact("human(socrates)");
+    engine.add_fact("human(plato)");
+
+    let solutions: Vec<_> = engine.query("mortal(Who)").unwrap().collect();
+
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0].get("Who"), Some(&Term::atom("socrates")));
+    let proof = solutions[0].proof();
+    assert_eq!(proof.rules_used(), vec!["mortality_rule"]);
+    assert_eq!(
+        proof.facts_used(),
+        vec![&Term::parse("human(socrates)").unwrap()]
+    );
+    assert_eq!(
+        proof.to_string(),
+        "mortal(socrates) [rule mortality_rule]\n  human(socrates) [fact]\n"
+    );
+}
+
+#[test]
+fn query_solutions_are_lazy() {
+    let engine = family();
+
+    // Left recursion never terminates without limits, but the first answer
+    // is available before the recursive branch is explored.
+    let first = engine.query("ancestor(ann, Y)").unwrap().next().unwrap();
+    assert_eq!(first.get("Y"), Some(&Term::atom("bob")));
+}
+
+#[test]
+fn query_depth_limit_cuts_recursion() {
+    let engine = family();
+
+    let options = QueryOptions::new().with_max_depth(3);
+    let mut solutions = engine.query_with("ancestor(ann, Y)", options).unwrap();
+    let answers: Vec<String> = solutions
+        .by_ref()
+        .map(|s| s.get("Y").unwrap().to_string())
+        .collect();
+
+    assert_eq!(answers, vec!["bob", "cid", "dee"]);
+    assert!(solutions.truncated());
+}
+
+#[test]
+fn comparisons_wait_for_bindings_in_both_directions() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from("rule hot: X > 25, temp(X) => hot(X).")
+        .unwrap();
+    engine.add_fact("temp(30)");
+    engine.add_fact("temp(20)");
+
+    for tabling in [false, true] {
+        let options = QueryOptions::new().with_tabling(tabling);
+        let solutions: Vec<_> = engine.query_with("hot(X)", options).unwrap().collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].get("X").unwrap().to_string(), "30");
+        // The proof keeps the conditions in the order they were written
+        let proof = solutions[0].proof();
+        assert_eq!(proof.children()[0].step(), &ProofStep::Builtin);
+        assert_eq!(proof.children()[1].step(), &ProofStep::Fact);
+    }
+
+    engine.infer();
+    assert!(engine.has_fact("hot(30)"));
+    assert!(!engine.has_fact("hot(20)"));
+}
+
+#[test]
+fn tabled_query_handles_left_recursion() {
+    let engine = family();
+
+    let options = QueryOptions::new().with_tabling(true);
+    let mut answers: Vec<String> = engine
+        .query_with("ancestor(X, dee)", options)
+        .unwrap()
+        .map(|s| s.get("X").unwrap().to_string())
+        .collect();
+    answers.sort();
+
+    assert_eq!(answers, vec!["ann", "bob", "cid"]);
+}
+
+#[test]
+fn tabled_query_on_cyclic_graph() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("path_edge")
+            .with_condition("edge(X, Y)")
+            .with_conclusion("path(X, Y)"),
+    );
+    engine.add_rule(
+        Rule::new("path_step")
+            .with_condition("edge(X, Z)")
+            .with_condition("path(Z, Y)")
+            .with_conclusion("path(X, Y)"),
+    );
+    engine.add_rule(
+        Rule::new("mutual")
+            .with_condition("path(X, Y)")
+            .with_condition("path(Y, X)")
+            .with_conclusion("mutual(X, Y)"),
+    );
+    for fact in ["edge(a, b)", "edge(b, c)", "edge(c, a)", "edge(c, d)"] {
+        engine.add_fact(fact);
+    }
+
+    let options = QueryOptions::new().with_tabling(true);
+    assert_eq!(engine.query_with("path(a, Y)", options).unwrap().count(), 4);
+    assert_eq!(
+        engine.query_with("mutual(X, Y)", options).unwrap().count(),
+        9
+    );
+    assert!(engine
+        .query_with("mutual(a, d)", options)
+        .unwrap()
+        .next()
+        .is_none());
+}
+
+#[test]
+fn incremental_matching_follows_assert_and_retract() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("alert")
+            .with_condition("sensor(S, hot)")
+            .with_condition("zone(S, Z)")
+            .with_conclusion("alert(Z)"),
+    );
+    engine.add_fact("zone(s1, kitchen)");
+    engine.add_fact("zone(s2, garage)");
+    engine.add_fact("sensor(s1, hot)");
+    assert_eq!(engine.infer(), vec!["alert(kitchen)"]);
+
+    // A retracted fact no longer supports new matches
+    assert!(engine.retract_fact("zone(s2, garage)"));
+    engine.add_fact("sensor(s2, hot)");
+    assert!(engine.infer().is_empty());
+
+    engine.add_fact("zone(s2, garage)");
+    assert_eq!(engine.infer(), vec!["alert(garage)"]);
+    assert!(!engine.retract_fact("zone(s3, attic)"));
+}
+
+#[test]
+fn matching_strategies_agree() {
+    let build = |strategy| {
+        let mut engine = ReasoningEngine::with_matching(strategy);
+        engine.add_fact("edge(a, b)");
+        engine.add_fact("edge(b, c)");
+        engine.add_fact("edge(c, d)");
+        engine.add_rule(
+            Rule::new("path_edge")
+                .with_condition("edge(X, Y)")
+                .with_conclusion("path(X, Y)"),
+        );
+        engine.add_rule(
+            Rule::new("path_step")
+                .with_condition("path(X, Y)")
+                .with_condition("path(Y, Z)")
+                .with_conclusion("path(X, Z)"),
+        );
+        let mut derived = engine.infer();
+        derived.sort();
+        derived
+    };
+
+    let naive = build(MatchStrategy::Naive);
+    assert_eq!(naive.len(), 6);
+    assert_eq!(naive, build(MatchStrategy::Incremental));
+}
+
+fn fired_rules(engine: &mut ReasoningEngine) -> Vec<String> {
+    let mut order = Vec::new();
+    while let Some(derived) = engine.fire_next() {
+        order.extend(derived);
+    }
+    order
+}
+
+#[test]
+fn salience_orders_agenda() {
+    for strategy in [MatchStrategy::Naive, MatchStrategy::Incremental] {
+        let mut engine = ReasoningEngine::with_matching(strategy);
+        engine.add_rule(
+            Rule::new("low")
+                .with_condition("go")
+                .with_conclusion("low_done"),
+        );
+        engine.add_rule(
+            Rule::new("high")
+                .with_condition("go")
+                .with_conclusion("high_done")
+                .with_salience(10),
+        );
+        engine.add_fact("go");
+
+        assert_eq!(engine.agenda(), vec!["high", "low"]);
+        assert_eq!(fired_rules(&mut engine), vec!["high_done", "low_done"]);
+    }
+}
+
+#[test]
+fn recency_strategies() {
+    let build = |conflict| {
+        let mut engine = ReasoningEngine::new();
+        engine.set_conflict_strategy(conflict);
+        engine.add_rule(
+            Rule::new("first")
+                .with_condition("old")
+                .with_condition("new")
+                .with_conclusion("by_first"),
+        );
+        engine.add_rule(
+            Rule::new("second")
+                .with_condition("new")
+                .with_condition("old")
+                .with_conclusion("by_second"),
+        );
+        engine.add_rule(
+            Rule::new("single")
+                .with_condition("new")
+                .with_conclusion("by_single"),
+        );
+        engine.add_fact("old");
+        engine.add_fact("new");
+        engine
+    };
+
+    assert_eq!(
+        build(ConflictStrategy::Fifo).agenda(),
+        vec!["first", "second", "single"]
+    );
+    // LEX: both two-condition rules match the same facts; longer match wins
+    assert_eq!(
+        build(ConflictStrategy::Lex).agenda(),
+        vec!["first", "second", "single"]
+    );
+    // MEA: the first condition decides, so `second` and `single` lead
+    assert_eq!(
+        build(ConflictStrategy::Mea).agenda(),
+        vec!["second", "single", "first"]
+    );
+    assert_eq!(
+        build(ConflictStrategy::Specificity).agenda(),
+        vec!["first", "second", "single"]
+    );
+}
+
+#[test]
+fn refraction_prevents_refiring() {
+    let mut engine = ReasoningEngine::with_matching(MatchStrategy::Naive);
+    engine.add_rule(
+        Rule::new("r")
+            .with_condition("p(X)")
+            .with_conclusion("q(X)"),
+    );
+    engine.add_fact("p(a)");
+
+    assert_eq!(engine.infer(), vec!["q(a)"]);
+    assert!(engine.agenda().is_empty());
+
+    // The conclusion stays while p(a) supports it, and the match does not refire
+    engine.retract_fact("q(a)");
+    assert!(engine.has_fact("q(a)"));
+    assert!(engine.fire_next().is_none());
+}
+
+#[test]
+fn datalog_recursion_and_negation() {
+    let mut program = DatalogProgram::new();
+    for fact in [
+        "node(a)",
+        "node(b)",
+        "node(c)",
+        "node(d)",
+        "edge(a, b)",
+        "edge(b, c)",
+    ] {
+        program.add_fact(fact).unwrap();
+    }
+    program.add_rule(
+        Rule::new("base")
+            .with_condition("edge(X, Y)")
+            .with_conclusion("path(X, Y)"),
+    );
+    program.add_rule(
+        Rule::new("step")
+            .with_condition("path(X, Y)")
+            .with_condition("edge(Y, Z)")
+            .with_conclusion("path(X, Z)"),
+    );
+    program.add_rule(
+        Rule::new("unreachable")
+            .with_condition("node(Y)")
+            .with_condition("not path(a, Y)")
+            .with_conclusion("unreachable(Y)"),
+    );
+
+    let model = program.evaluate().unwrap();
+    assert!(model.contains("path(a, c)"));
+    assert!(!model.contains("path(c, a)"));
+    let mut unreachable: Vec<String> = model
+        .query("unreachable(Y)")
+        .unwrap()
+        .iter()
+        .map(|s| s.resolve("Y").unwrap().to_string())
+        .collect();
+    unreachable.sort();
+    assert_eq!(unreachable, vec!["a", "d"]);
+}
+
+#[test]
+fn datalog_aggregates_group_by_remaining_arguments() {
+    let mut program = DatalogProgram::new();
+    for fact in [
+        "salary(ann, sales, 100)",
+        "salary(bob, sales, 50)",
+        "salary(cid, it, 70)",
+    ] {
+        program.add_fact(fact).unwrap();
+    }
+    program.add_rule(
+        Rule::new("totals")
+            .with_condition("salary(E, D, S)")
+            .with_conclusion("total(D, sum(S))")
+            .with_conclusion("staff(D, count(E))")
+            .with_conclusion("top(D, max(S))"),
+    );
+
+    let model = program.evaluate().unwrap();
+    assert!(model.contains("total(sales, 150)"));
+    assert!(model.contains("total(it, 70)"));
+    assert!(model.contains("staff(sales, 2)"));
+    assert!(model.contains("top(sales, 100)"));
+
+    // Counting distinct employees, not distinct matches of the body
+    let mut program = DatalogProgram::new();
+    for fact in [
+        "works(ann, sales)",
+        "works(bob, sales)",
+        "skill(ann, excel)",
+        "skill(ann, sql)",
+        "skill(bob, excel)",
+    ] {
+        program.add_fact(fact).unwrap();
+    }
+    program.add_rule(
+        Rule::new("headcount")
+            .with_condition("works(E, D)")
+            .with_condition("skill(E, S)")
+            .with_conclusion("staff(D, count(E))"),
+    );
+    let model = program.evaluate().unwrap();
+    assert!(model.contains("staff(sales, 2)"));
+    assert!(!model.contains("staff(sales, 3)"));
+}
+
+#[test]
+fn datalog_rejects_negation_through_recursion() {
+    let mut program = DatalogProgram::new();
+    program.add_fact("move(a, b)").unwrap();
+    program.add_rule(
+        Rule::new("win")
+            .with_condition("move(X, Y)")
+            .with_condition("not wins(Y)")
+            .with_conclusion("wins(X)"),
+    );
+
+    match program.evaluate() {
+        Err(CognitionError::ReasoningError(message)) => {
+            assert!(message.contains("win -> win"), "{}", message)
+        }
+        other => panic!(
+            "expected stratification error, got {:?}",
+            other.map(|m| m.len())
+        ),
+    }
+}
+
+#[test]
+fn rule_language_with_comparisons() {
+    let source = "
+        % Thermostat rules
+        rule hot: temperature(X), X > 25 => hot.
+        rule alarm salience 10: hot, humidity(H), H >= 80 => alarm(high), notify. // loud
+    ";
+    let rules = parse_rules(source).unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].conditions(), ["temperature(X)", "X > 25"]);
+    assert_eq!(rules[1].salience(), 10);
+    assert_eq!(rules[1].conclusions(), ["alarm(high)", "notify"]);
+
+    for strategy in [MatchStrategy::Naive, MatchStrategy::Incremental] {
+        let mut engine = ReasoningEngine::with_matching(strategy);
+        assert_eq!(engine.add_rules_from(source).unwrap(), 2);
+        engine.add_fact("temperature(20)");
+        engine.add_fact("humidity(85)");
+        assert!(engine.infer().is_empty());
+
+        engine.add_fact("temperature(30)");
+        assert_eq!(engine.infer(), vec!["hot", "alarm(high)", "notify"]);
+    }
+
+    let mut engine = ReasoningEngine::new();
+    engine.add_rules_from(source).unwrap();
+    engine.add_fact("temperature(31)");
+    let solution = engine.query("hot").unwrap().next().unwrap();
+    assert_eq!(solution.proof().facts_used().len(), 1);
+}
+
+#[test]
+fn rule_language_reports_positions() {
+    let source = "rule ok: a => b.\nrule broken: p(X,\n  => q.";
+    let error = parse_rules(source).unwrap_err();
+    assert_eq!((error.line(), error.column()), (3, 3));
+
+    let error = parse_rules("rule r: a => b").unwrap_err();
+    assert_eq!(error.message(), "expected ',' or '.'");
+    assert_eq!((error.line(), error.column()), (1, 15));
+
+    let converted: CognitionError = error.into();
+    assert!(matches!(converted, CognitionError::SyntaxError(_)));
+}
+
+#[test]
+fn rule_files_load_in_order() {
+    let dir = std::env::temp_dir().join(format!("cognition-rules-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let first = dir.join("base.rules");
+    let second = dir.join("extra.rules");
+    std::fs::write(&first, "rule a: p => q.\n").unwrap();
+    std::fs::write(&second, "rule b: q => r.\nrule c q => s.\n").unwrap();
+
+    let error = load_rules([&first, &second]).unwrap_err();
+    match error {
+        CognitionError::SyntaxError(e) => {
+            assert!(e.file().unwrap().ends_with("extra.rules"));
+            assert_eq!(e.line(), 2);
+        }
+        other => panic!("expected syntax error, got {}", other),
+    }
+
+    std::fs::write(&second, "rule b: q => r.\n").unwrap();
+    let rules = load_rules([&first, &second]).unwrap();
+    let names: Vec<&str> = rules.iter().map(|r| r.name()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn truth_maintenance_retracts_and_restores_conclusions() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule hot: temperature(X), X > 25 => hot.
+             rule alarm: hot, humid => alarm.",
+        )
+        .unwrap();
+    engine.add_fact("temperature(30)");
+    engine.add_fact("humid");
+    assert_eq!(engine.infer(), vec!["hot", "alarm"]);
+
+    let why = engine.why("alarm").unwrap();
+    assert_eq!(why.rules_used(), vec!["alarm", "hot"]);
+    assert_eq!(why.facts_used().len(), 2);
+    assert_eq!(engine.justifications("hot")[0].rule(), "hot");
+    assert!(engine.why("humid").unwrap().children().is_empty());
+
+    assert!(engine.retract_fact("temperature(30)"));
+    assert!(!engine.has_fact("hot"));
+    assert!(!engine.has_fact("alarm"));
+    assert!(engine.why("alarm").is_none());
+
+    // Support returns without re-running inference
+    engine.add_fact("temperature(30)");
+    assert!(engine.has_fact("hot"));
+    assert!(engine.has_fact("alarm"));
+}
+
+#[test]
+fn truth_maintenance_needs_well_founded_support() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule pq: p => q.
+             rule qp: q => p.
+             rule rq: r => q.",
+        )
+        .unwrap();
+    engine.add_fact("p");
+    engine.add_fact("r");
+    engine.infer();
+
+    // q keeps independent support from r
+    engine.retract_fact("p");
+    assert!(engine.has_fact("q"));
+    assert!(engine.has_fact("p"));
+
+    // p and q only support each other now
+    engine.retract_fact("r");
+    assert!(!engine.has_fact("q"));
+    assert!(!engine.has_fact("p"));
+}
+
+#[test]
+fn belief_base_changes_propagate_to_conclusions() {
+    let mut beliefs = BeliefBase::new();
+    beliefs.add(Belief::new("temperature", "30"));
+    beliefs.add(Belief::new("door", "open"));
+
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from("rule draft: door(open), temperature(T), T < 35 => draft(cold).")
+        .unwrap();
+    engine.sync_beliefs(&beliefs);
+    engine.infer();
+    let derived = engine.derived_beliefs();
+    assert_eq!(derived.len(), 1);
+    assert_eq!(
+        (derived[0].key(), derived[0].as_str()),
+        ("draft", Some("cold"))
+    );
+
+    beliefs.remove("door");
+    let retracted = engine.sync_beliefs(&beliefs);
+    assert_eq!(retracted, vec!["door(open)", "draft(cold)"]);
+    assert!(engine.derived_beliefs().is_empty());
+}
+
+#[test]
+fn certainty_factors_propagate_through_rules() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule storm strength 0.8: pressure(low), wind(high) => storm.
+             rule forecast strength 0.5: radio(warning) => storm.
+             rule shelter: storm => shelter.",
+        )
+        .unwrap();
+    engine.add_fact_with_certainty("pressure(low)", 0.9);
+    engine.add_fact_with_certainty("wind(high)", 0.5);
+    engine.infer();
+    assert!((engine.certainty("storm") - 0.4).abs() < 1e-9);
+
+    engine.set_certainty_combination(CertaintyCombination::Product);
+    assert!((engine.certainty("storm") - 0.36).abs() < 1e-9);
+    engine.set_certainty_combination(CertaintyCombination::Min);
+
+    // A second derivation adds evidence, MYCIN style
+    engine.add_fact("radio(warning)");
+    engine.infer();
+    let combined = combine_evidence(0.4, 0.5);
+    assert!((combined - 0.7).abs() < 1e-9);
+    assert!((engine.certainty("storm") - combined).abs() < 1e-9);
+    assert!((engine.certainty("shelter") - combined).abs() < 1e-9);
+
+    let shelter = engine
+        .derived_beliefs()
+        .into_iter()
+        .find(|b| b.key() == "shelter")
+        .unwrap();
+    assert_eq!(shelter.value(), "true");
+    assert!((shelter.certainty() - combined).abs() < 1e-9);
+    assert_eq!(engine.certainty("unknown"), 0.0);
+}
+
+#[test]
+fn certainty_threshold_blocks_weak_conclusions() {
+    let mut engine = ReasoningEngine::new();
+    engine.set_certainty_threshold(0.5);
+    engine.add_rule(
+        Rule::new("guess")
+            .with_condition("hint(X)")
+            .with_conclusion("answer(X)")
+            .with_strength(0.6),
+    );
+    engine.add_fact_with_certainty("hint(a)", 0.9);
+    engine.add_fact_with_certainty("hint(b)", 0.7);
+    assert_eq!(engine.infer(), vec!["answer(a)"]);
+    assert!((engine.certainty("answer(a)") - 0.54).abs() < 1e-9);
+
+    // Stronger evidence lets the held-back conclusion through
+    engine.add_fact_with_certainty("hint(b)", 1.0);
+    assert!(engine.has_fact("answer(b)"));
+
+    // Weaker evidence withdraws conclusions that no longer qualify
+    engine.add_rule(
+        Rule::new("confirm")
+            .with_condition("answer(X)")
+            .with_conclusion("confirmed(X)"),
+    );
+    engine.infer();
+    assert!(engine.has_fact("confirmed(a)"));
+    engine.add_fact_with_certainty("hint(a)", 0.5);
+    assert!(!engine.has_fact("answer(a)"));
+    assert!(!engine.has_fact("confirmed(a)"));
+    assert_eq!(engine.certainty("answer(a)"), 0.0);
+    assert!(engine.has_fact("confirmed(b)"));
+
+    // and restores them when it recovers
+    engine.add_fact_with_certainty("hint(a)", 0.9);
+    assert!(engine.has_fact("answer(a)"));
+    assert!(engine.has_fact("confirmed(a)"));
+}
+
+#[test]
+fn membership_functions() {
+    let triangle = Membership::triangular(0.0, 5.0, 10.0);
+    assert_eq!(triangle.degree(2.5), 0.5);
+    assert_eq!(triangle.degree(5.0), 1.0);
+    assert_eq!(triangle.degree(11.0), 0.0);
+
+    let shoulder = Membership::trapezoidal(20.0, 30.0, 40.0, 40.0);
+    assert_eq!(shoulder.degree(25.0), 0.5);
+    assert_eq!(shoulder.degree(40.0), 1.0);
+
+    let bell = Membership::gaussian(0.0, 1.0);
+    assert_eq!(bell.degree(0.0), 1.0);
+    assert!((bell.degree(1.0) - (-0.5f64).exp()).abs() < 1e-12);
+}
+
+fn fan_controller() -> FuzzySystem {
+    let temperature = LinguisticVariable::new("temperature", 0.0, 40.0)
+        .with_term("cold", Membership::trapezoidal(0.0, 0.0, 10.0, 20.0))
+        .with_term("warm", Membership::triangular(10.0, 20.0, 30.0))
+        .with_term("hot", Membership::trapezoidal(20.0, 30.0, 40.0, 40.0));
+    let fan = LinguisticVariable::new("fan", 0.0, 100.0)
+        .with_term("slow", Membership::triangular(0.0, 0.0, 50.0))
+        .with_term("fast", Membership::triangular(50.0, 100.0, 100.0));
+    FuzzySystem::new()
+        .with_input(temperature)
+        .with_output(fan)
+        .with_rule(
+            FuzzyRule::new("cool")
+                .with_condition("temperature", "cold")
+                .with_conclusion("fan", "slow"),
+        )
+        .with_rule(
+            FuzzyRule::new("blow")
+                .with_condition("temperature", "hot")
+                .with_conclusion("fan", "fast"),
+        )
+}
+
+#[test]
+fn mamdani_inference_with_defuzzification() {
+    let system = fan_controller();
+    let inputs = HashMap::from([("temperature".to_string(), 35.0)]);
+    let centroid = system.evaluate(&inputs).unwrap()["fan"];
+    assert!((centroid - 83.33).abs() < 0.5, "{}", centroid);
+
+    let maxima = system
+        .clone()
+        .with_defuzzification(Defuzzification::MeanOfMaxima)
+        .evaluate(&inputs)
+        .unwrap()["fan"];
+    assert!((maxima - 100.0).abs() < 1e-9);
+
+    let bisector = system
+        .with_defuzzification(Defuzzification::Bisector)
+        .evaluate(&inputs)
+        .unwrap()["fan"];
+    assert!(bisector > 70.0 && bisector < 90.0, "{}", bisector);
+}
+
+#[test]
+fn sugeno_inference_from_beliefs() {
+    let system = FuzzySystem::new()
+        .with_input(
+            LinguisticVariable::new("temperature", 0.0, 40.0)
+                .with_term("cold", Membership::trapezoidal(0.0, 0.0, 10.0, 30.0))
+                .with_term("hot", Membership::trapezoidal(10.0, 30.0, 40.0, 40.0)),
+        )
+        .with_rule(
+            FuzzyRule::new("cool")
+                .with_condition("temperature", "cold")
+                .with_function("fan", 10.0, &[]),
+        )
+        .with_rule(
+            FuzzyRule::new("blow")
+                .with_condition("temperature", "hot")
+                .with_function("fan", 0.0, &[("temperature", 2.0)]),
+        );
+
+    let mut beliefs = BeliefBase::new();
+    beliefs.add(Belief::new("temperature", "20"));
+    // Both rules fire at 0.5: (10 + 40) / 2
+    let outputs = system.evaluate_beliefs(&beliefs).unwrap();
+    assert!((outputs["fan"] - 25.0).abs() < 1e-9);
+
+    beliefs.add(Belief::new("temperature", "warm"));
+    assert!(system.evaluate_beliefs(&beliefs).is_err());
+}
+
+fn sprinkler_network() -> BayesianNetwork {
+    let mut network = BayesianNetwork::new();
+    network
+        .add_node("rain", &["yes", "no"], &[], vec![vec![0.2, 0.8]])
+        .unwrap();
+    network
+        .add_node(
+            "sprinkler",
+            &["on", "off"],
+            &["rain"],
+            vec![vec![0.01, 0.99], vec![0.4, 0.6]],
+        )
+        .unwrap();
+    network
+        .add_node(
+            "grass",
+            &["wet", "dry"],
+            &["sprinkler", "rain"],
+            vec![
+                vec![0.99, 0.01],
+                vec![0.9, 0.1],
+                vec![0.8, 0.2],
+                vec![0.0, 1.0],
+            ],
+        )
+        .unwrap();
+    network
+}
+
+#[test]
+fn bayesian_network_exact_inference() {
+    let mut network = sprinkler_network();
+    let prior = network.query("grass", &Evidence::new()).unwrap();
+    assert!((prior.probability("wet").unwrap() - 0.44838).abs() < 1e-5);
+
+    let evidence = Evidence::from([("grass".to_string(), "wet".to_string())]);
+    let posterior = network.query("rain", &evidence).unwrap();
+    assert!((posterior.probability("yes").unwrap() - 0.35769).abs() < 1e-4);
+    assert_eq!(posterior.most_likely().0, "no");
+
+    assert!(network
+        .add_node("bad", &["a", "b"], &["rain"], vec![vec![0.5, 0.5]])
+        .is_err());
+    let impossible = Evidence::from([
+        ("sprinkler".to_string(), "off".to_string()),
+        ("rain".to_string(), "no".to_string()),
+        ("grass".to_string(), "wet".to_string()),
+    ]);
+    assert!(network.query("rain", &impossible).is_err());
+
+    // Observing the target itself gives a point mass, as sampling does
+    let observed = Evidence::from([("rain".to_string(), "yes".to_string())]);
+    let certain = network.query("rain", &observed).unwrap();
+    assert_eq!(certain.probability("yes"), Some(1.0));
+    let sampled = network
+        .likelihood_weighting("rain", &observed, 100, 7)
+        .unwrap();
+    assert_eq!(sampled, certain);
+}
+
+#[test]
+fn bayesian_network_sampling_is_seeded() {
+    let network = sprinkler_network();
+    let evidence = Evidence::from([("grass".to_string(), "wet".to_string())]);
+
+    let weighted = network
+        .likelihood_weighting("rain", &evidence, 20_000, 7)
+        .unwrap();
+    assert!((weighted.probability("yes").unwrap() - 0.3577).abs() < 0.03);
+    assert_eq!(
+        weighted,
+        network
+            .likelihood_weighting("rain", &evidence, 20_000, 7)
+            .unwrap()
+    );
+
+    // The deterministic zero in the grass table makes the chain mix slowly
+    let gibbs = network.gibbs("rain", &evidence, 200_000, 500, 7).unwrap();
+    assert!((gibbs.probability("yes").unwrap() - 0.3577).abs() < 0.03);
+}
+
+#[test]
+fn bayesian_posteriors_from_beliefs() {
+    let network = sprinkler_network();
+    let mut beliefs = BeliefBase::new();
+    beliefs.add(Belief::new("grass", "wet"));
+    beliefs.add(Belief::new("weather", "mild"));
+
+    let posteriors = network.posterior_beliefs(&beliefs).unwrap();
+    assert_eq!(posteriors.len(), 2);
+    let rain = posteriors.iter().find(|b| b.key() == "rain").unwrap();
+    assert_eq!(rain.value(), "no");
+    assert!((rain.certainty() - 0.6423).abs() < 1e-3);
+}
+
+#[test]
+fn argumentation_semantics() {
+    // Nixon diamond: a and b attack each other, c is attacked by b
+    let mut framework = ArgumentationFramework::new();
+    for name in ["a", "b", "c", "d"] {
+        framework.add_argument(name);
+    }
+    framework.add_attack("a", "b").unwrap();
+    framework.add_attack("b", "a").unwrap();
+    framework.add_attack("b", "c").unwrap();
+    assert!(framework.add_attack("a", "missing").is_err());
+
+    assert_eq!(framework.grounded(), vec!["d"]);
+    assert_eq!(
+        framework.extensions(Semantics::Preferred),
+        vec![vec!["a", "c", "d"], vec!["b", "d"]]
+    );
+    assert_eq!(framework.extensions(Semantics::Stable).len(), 2);
+    assert_eq!(framework.accepted(Semantics::Preferred), vec!["d"]);
+    assert_eq!(
+        framework.credulously_accepted(Semantics::Preferred),
+        vec!["a", "b", "c", "d"]
+    );
+
+    // An odd cycle has no stable extension
+    let mut odd = ArgumentationFramework::new();
+    for name in ["x", "y", "z"] {
+        odd.add_argument(name);
+    }
+    odd.add_attack("x", "y").unwrap();
+    odd.add_attack("y", "z").unwrap();
+    odd.add_attack("z", "x").unwrap();
+    assert!(odd.extensions(Semantics::Stable).is_empty());
+    assert_eq!(
+        odd.extensions(Semantics::Preferred),
+        vec![Vec::<&str>::new()]
+    );
+
+    // Unattacked arguments do not multiply the search
+    let mut wide = framework.clone();
+    for i in 0..30 {
+        wide.add_argument(format!("u{}", i));
+    }
+    let preferred = wide.extensions(Semantics::Preferred);
+    assert_eq!(preferred.len(), 2);
+    assert!(preferred.iter().all(|e| e.contains(&"u29")));
+    assert_eq!(preferred[0].len(), 33);
+    assert_eq!(wide.extensions(Semantics::Stable).len(), 2);
+    assert_eq!(wide.accepted(Semantics::Preferred).len(), 31);
+
+    let mut engine = ReasoningEngine::new();
+    for i in 0..30 {
+        engine.add_fact(format!("reading(s{})", i));
+    }
+    let resolution = ArgumentSet::from_engine(&engine).resolve(Semantics::Preferred);
+    assert_eq!(resolution.accepted().len(), 30);
+}
+
+#[test]
+fn structured_arguments_rebut_and_undercut() {
+    let rules = parse_rules(
+        "rule birds_fly: bird(X) => flies(X).
+         rule penguins_walk: penguin(X) => neg(flies(X)).
+         rule penguins_are_birds: penguin(X) => bird(X).
+         rule sensor_fault: broken(Sensor) => undercut(sensor_reading).
+         rule sensor_reading: reading(hot) => hot.",
+    )
+    .unwrap();
+    let arguments = ArgumentSet::build(
+        &rules,
+        &[
+            "bird(tweety)",
+            "penguin(opus)",
+            "reading(hot)",
+            "broken(s1)",
+        ],
+    )
+    .unwrap();
+
+    // Opus flies and does not fly; neither side wins sceptically
+    let resolution = arguments.resolve(Semantics::Grounded);
+    assert!(resolution.is_accepted("flies(tweety)"));
+    assert!(resolution.is_accepted("bird(opus)"));
+    assert!(!resolution.is_accepted("flies(opus)"));
+    assert!(!resolution.is_accepted("neg(flies(opus))"));
+    assert!(resolution
+        .undecided()
+        .contains(&Term::parse("flies(opus)").unwrap()));
+
+    // The broken sensor undercuts the reading rule
+    assert!(!resolution.is_accepted("hot"));
+    let defeat = resolution
+        .defeated()
+        .iter()
+        .find(|d| d.conclusion().to_string() == "hot")
+        .unwrap();
+    assert_eq!(defeat.kind(), AttackKind::Undercut);
+    assert_eq!(defeat.attacker().to_string(), "undercut(sensor_reading)");
+
+    // Preferred semantics pick a side in each extension, so still undecided
+    let preferred = arguments.resolve(Semantics::Preferred);
+    assert!(!preferred.is_accepted("flies(opus)"));
+    assert!(preferred.is_accepted("flies(tweety)"));
+}
+
+#[test]
+fn structured_arguments_from_engine() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule report_a: witness_a => guilty.
+             rule report_b: witness_b => neg(guilty).
+             rule discredit: liar(b) => undercut(report_b).",
+        )
+        .unwrap();
+    engine.add_fact("witness_a");
+    engine.add_fact("witness_b");
+    engine.add_fact("liar(b)");
+    engine.infer();
+
+    let resolution = ArgumentSet::from_engine(&engine).resolve(Semantics::Grounded);
+    assert!(resolution.is_accepted("guilty"));
+    let reasons: Vec<_> = resolution
+        .defeated()
+        .iter()
+        .map(|d| (d.conclusion().to_string(), d.kind()))
+        .collect();
+    assert!(reasons.contains(&("neg(guilty)".to_string(), AttackKind::Undercut)));
+}
+
+#[test]
+fn defeasible_rules_with_superiority() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule penguins_are_birds: penguin(X) => bird(X).
+             rule birds_fly defeasible: bird(X) => flies(X).
+             rule penguins_dont_fly defeasible over birds_fly: penguin(X) => neg(flies(X)).
+             rule injured defeater: injured(X) => neg(flies(X)).",
+        )
+        .unwrap();
+    engine.add_fact("bird(tweety)");
+    engine.add_fact("penguin(opus)");
+    engine.add_fact("bird(polly)");
+    engine.add_fact("injured(polly)");
+
+    // Strict rules still chain as before; defeasible ones do not fire
+    assert_eq!(engine.infer(), vec!["bird(opus)"]);
+    assert!(!engine.has_fact("flies(tweety)"));
+
+    let model = engine.defeasible_model();
+    assert!(model.is_definite("bird(opus)"));
+    assert!(!model.is_definite("flies(tweety)"));
+    assert!(model.is_defeasible("flies(tweety)"));
+    assert!(model.is_defeasible("neg(flies(opus))"));
+    assert!(model.is_refuted("flies(opus)"));
+    // A defeater blocks a conclusion without proving the opposite
+    assert!(model.is_refuted("flies(polly)"));
+    assert!(model.is_refuted("neg(flies(polly))"));
+    assert!(model.is_refuted("flies(nobody)"));
+}
+
+#[test]
+fn defeasible_ambiguity_is_blocked() {
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(
+        Rule::new("quakers_pacifist")
+            .with_kind(RuleKind::Defeasible)
+            .with_condition("quaker(X)")
+            .with_conclusion("pacifist(X)"),
+    );
+    engine.add_rule(
+        Rule::new("republicans_not_pacifist")
+            .with_kind(RuleKind::Defeasible)
+            .with_condition("republican(X)")
+            .with_conclusion("neg(pacifist(X))"),
+    );
+    engine.add_rule(
+        Rule::new("pacifists_protest")
+            .with_kind(RuleKind::Defeasible)
+            .with_condition("pacifist(X)")
+            .with_conclusion("protests(X)"),
+    );
+    engine.add_fact("quaker(nixon)");
+    engine.add_fact("republican(nixon)");
+    engine.add_fact("quaker(penn)");
+
+    let model = engine.defeasible_model();
+    assert!(!model.is_defeasible("pacifist(nixon)"));
+    assert!(!model.is_defeasible("neg(pacifist(nixon))"));
+    assert!(model.is_refuted("protests(nixon)"));
+    assert!(model.is_defeasible("protests(penn)"));
+    assert_eq!(model.definite().len(), 3);
+}
+
+#[test]
+fn csp_solves_queens() {
+    let mut csp = Csp::new();
+    let columns: Vec<String> = (0..6).map(|i| format!("q{}", i)).collect();
+    for column in &columns {
+        csp.add_range(column.as_str(), 0..=5).unwrap();
+    }
+    for i in 0..6 {
+        for j in (i + 1)..6 {
+            let distance = (j - i) as i64;
+            csp.add_constraint(&columns[i], &columns[j], move |a, b| {
+                let (a, b): (i64, i64) = (a.parse().unwrap(), b.parse().unwrap());
+                (a - b).abs() != distance
+            })
+            .unwrap();
+        }
+    }
+    let names: Vec<&str> = columns.iter().map(String::as_str).collect();
+    csp.add_all_different(&names).unwrap();
+
+    let solutions = csp.solutions(usize::MAX);
+    assert_eq!(solutions.len(), 4);
+    let first = csp.solve().unwrap();
+    assert_eq!(first.variables().len(), 6);
+    assert!(solutions
+        .iter()
+        .any(|s| names.iter().all(|q| s.get(q) == first.get(q))));
+}
+
+#[test]
+fn csp_schedules_with_sums_and_propagation() {
+    let mut csp = Csp::new();
+    for meeting in ["standup", "review", "planning"] {
+        csp.add_variable(meeting, ["9", "10", "11"]).unwrap();
+    }
+    csp.add_all_different(&["standup", "review", "planning"])
+        .unwrap();
+    csp.add_constraint("standup", "review", |a, b| {
+        a.parse::<i64>().unwrap() < b.parse::<i64>().unwrap()
+    })
+    .unwrap();
+    csp.add_constraint("review", "planning", |a, b| {
+        a.parse::<i64>().unwrap() < b.parse::<i64>().unwrap()
+    })
+    .unwrap();
+
+    assert!(csp.propagate());
+    assert_eq!(csp.domain("standup").unwrap(), ["9"]);
+    let schedule = csp.solve().unwrap();
+    assert!(schedule.matches("review", "10"));
+    assert_eq!(csp.solutions(10).len(), 1);
+
+    // Assign crew sizes that fit a budget
+    let mut crews = Csp::new();
+    for team in ["a", "b", "c"] {
+        crews.add_range(team, 1..=4).unwrap();
+    }
+    crews
+        .add_sum(&["a", "b", "c"], SumBound::Equal(10))
+        .unwrap();
+    crews.add_sum(&["a", "b"], SumBound::AtMost(6)).unwrap();
+    let solutions = crews.solutions(usize::MAX);
+    assert!(!solutions.is_empty());
+    for solution in &solutions {
+        let total: i64 = ["a", "b", "c"]
+            .iter()
+            .map(|t| solution.get(t).unwrap().parse::<i64>().unwrap())
+            .sum();
+        assert_eq!(total, 10);
+        assert!(solution.matches("c", "4"));
+    }
+    assert_eq!(solutions.len(), 3);
+
+    crews.add_sum(&["a"], SumBound::AtLeast(5)).unwrap();
+    assert!(crews.solve().is_none());
+    assert!(crews.add_all_different(&["a", "missing"]).is_err());
+    assert!(crews.add_variable("a", ["1"]).is_err());
+
+    let mut named = Csp::new();
+    named.add_variable("lead", ["alice", "bob"]).unwrap();
+    assert!(named.add_sum(&["lead"], SumBound::Equal(1)).is_err());
+}
+
+fn fleet_ontology() -> Ontology {
+    let mut ontology = Ontology::new();
+    ontology.add_subclass("vehicle", "asset").unwrap();
+    ontology.add_subclass("truck", "vehicle").unwrap();
+    ontology.add_subclass("drone", "vehicle").unwrap();
+    ontology.add_subclass("drone", "aircraft").unwrap();
+    ontology.add_instance("t1", "truck");
+    ontology.add_instance("d1", "drone");
+    ontology.set_property("vehicle", "wheels", "4");
+    ontology.set_property("vehicle", "fuel", "diesel");
+    ontology.set_property("drone", "wheels", "0");
+    ontology.set_property("d1", "fuel", "battery");
+    ontology.add_disjoint("truck", "aircraft");
+    ontology
+}
+
+#[test]
+fn ontology_hierarchy_and_inheritance() {
+    let mut ontology = fleet_ontology();
+    assert!(ontology.is_subclass_of("truck", "asset"));
+    assert!(!ontology.is_subclass_of("asset", "truck"));
+    assert_eq!(
+        ontology.superclasses_of("drone"),
+        vec!["vehicle", "aircraft", "asset"]
+    );
+    assert_eq!(ontology.subclasses_of("vehicle"), vec!["truck", "drone"]);
+    assert!(ontology.is_instance_of("t1", "asset"));
+    assert_eq!(ontology.instances_of("vehicle"), vec!["t1", "d1"]);
+    assert!(ontology.add_subclass("asset", "truck").is_err());
+
+    // Nearest value wins; instances override their classes
+    assert_eq!(ontology.property("t1", "wheels"), Some("4"));
+    assert_eq!(ontology.property("d1", "wheels"), Some("0"));
+    assert_eq!(ontology.property("d1", "fuel"), Some("battery"));
+    assert_eq!(ontology.property("truck", "fuel"), Some("diesel"));
+    assert_eq!(ontology.property("t1", "colour"), None);
+    assert_eq!(ontology.properties("d1").len(), 2);
+
+    assert!(ontology.are_disjoint("truck", "drone"));
+    assert!(!ontology.are_disjoint("truck", "vehicle"));
+    assert!(ontology.violations().is_empty());
+    ontology.add_instance("t1", "aircraft");
+    let violations = ontology.violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].entity(), "t1");
+    assert_eq!(violations[0].classes(), ("truck", "aircraft"));
+}
+
+#[test]
+fn ontology_in_rule_conditions() {
+    let mut engine = ReasoningEngine::new();
+    assert!(engine.add_ontology(&fleet_ontology()) > 0);
+    engine
+        .add_rules_from(
+            "rule needs_road: instance_of(X, vehicle), property(X, wheels, W), W > 0 => road(X).
+             rule charge: instance_of(X, vehicle), property(X, fuel, battery) => charger(X).",
+        )
+        .unwrap();
+    engine.infer();
+    assert!(engine.has_fact("road(t1)"));
+    assert!(!engine.has_fact("road(d1)"));
+    assert!(engine.has_fact("charger(d1)"));
+    assert!(engine.has_fact("subclass_of(truck, asset)"));
+    assert_eq!(engine.add_ontology(&fleet_ontology()), 0);
+
+    // Declaring a disjointness twice, either way round, stores it once
+    let mut ontology = fleet_ontology();
+    ontology.add_disjoint("aircraft", "truck");
+    ontology.add_disjoint("truck", "aircraft");
+    let disjoint = ontology
+        .to_terms()
+        .into_iter()
+        .filter(|t| t.to_string().starts_with("disjoint("))
+        .count();
+    assert_eq!(disjoint, 2);
+
+    // Syncing follows changes, retracting stale closure facts and their
+    // conclusions
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule charge: instance_of(X, vehicle), property(X, fuel, battery) => charger(X).",
+        )
+        .unwrap();
+    assert!(engine.sync_ontology(&ontology).is_empty());
+    engine.infer();
+    assert!(engine.has_fact("charger(d1)"));
+    ontology.set_property("d1", "fuel", "diesel");
+    let retracted = engine.sync_ontology(&ontology);
+    assert_eq!(retracted, vec!["property(d1,fuel,battery)", "charger(d1)"]);
+    assert!(engine.has_fact("property(d1, fuel, diesel)"));
+    assert!(!engine.has_fact("charger(d1)"));
+}
+
+fn snapshot(beliefs: &[(&str, &str)]) -> BeliefBase {
+    let mut base = BeliefBase::new();
+    for (key, value) in beliefs {
+        base.add(Belief::new(*key, *value));
+    }
+    base
+}
+
+#[test]
+fn temporal_monitor_reports_violation_step() {
+    let mut monitor = Monitor::new("always !(carrying && battery < 10)").unwrap();
+    assert_eq!(
+        monitor.observe(&snapshot(&[("battery", "50"), ("carrying", "true")])),
+        Verdict::Pending
+    );
+    assert_eq!(
+        monitor.observe(&snapshot(&[("battery", "8"), ("carrying", "false")])),
+        Verdict::Pending
+    );
+    assert_eq!(
+        monitor.observe(&snapshot(&[("battery", "9"), ("carrying", "true")])),
+        Verdict::Violated
+    );
+    let violation = monitor.violation().unwrap();
+    assert_eq!(violation.step(), 2);
+    assert_eq!(violation.property(), "always (!(carrying && battery < 10))");
+    // The verdict is final
+    assert_eq!(monitor.observe(&snapshot(&[])), Verdict::Violated);
+    assert_eq!(monitor.steps(), 4);
+
+    monitor.reset();
+    assert_eq!(monitor.observe(&snapshot(&[])), Verdict::Pending);
+    assert_eq!(monitor.finish(), Verdict::Satisfied);
+}
+
+#[test]
+fn temporal_future_and_past_operators() {
+    // Requests must be answered before the trace ends
+    let mut response = Monitor::new("G (request -> F status(done))").unwrap();
+    response.observe(&snapshot(&[("request", "true")]));
+    response.observe(&snapshot(&[("status", "busy")]));
+    assert_eq!(response.finish(), Verdict::Violated);
+    response.observe(&snapshot(&[("status", "done")]));
+    assert_eq!(response.finish(), Verdict::Satisfied);
+
+    let mut until = Monitor::new("charging until battery >= 90").unwrap();
+    assert_eq!(
+        until.observe(&snapshot(&[("charging", "true"), ("battery", "40")])),
+        Verdict::Pending
+    );
+    assert_eq!(
+        until.observe(&snapshot(&[("battery", "95")])),
+        Verdict::Satisfied
+    );
+
+    // Deliveries need an earlier pickup, and the door was closed just before
+    let mut past =
+        Monitor::new("always (delivered -> (once picked_up && previously door(closed)))").unwrap();
+    past.observe(&snapshot(&[("picked_up", "true"), ("door", "open")]));
+    past.observe(&snapshot(&[("door", "closed")]));
+    assert_eq!(
+        past.observe(&snapshot(&[("delivered", "true"), ("door", "open")])),
+        Verdict::Pending
+    );
+    assert_eq!(
+        past.observe(&snapshot(&[("delivered", "true"), ("door", "open")])),
+        Verdict::Violated
+    );
+    assert_eq!(past.violation().unwrap().step(), 3);
+
+    let mut since = Monitor::new("G (armed -> (!intrusion S armed_at))").unwrap();
+    since.observe(&snapshot(&[("armed_at", "true"), ("armed", "true")]));
+    since.observe(&snapshot(&[("armed", "true")]));
+    assert_eq!(
+        since.observe(&snapshot(&[("armed", "true"), ("intrusion", "true")])),
+        Verdict::Violated
+    );
+
+    let parsed = TemporalFormula::parse("next (a || b) -> historically c").unwrap();
+    assert_eq!(parsed.to_string(), "(!(next (a || b))) || (historically c)");
+    assert!(TemporalFormula::parse("once eventually a").is_err());
+    assert!(TemporalFormula::parse("always X > 3").is_err());
+    assert!(Monitor::new("a &&").is_err());
+}
+
+#[test]
+fn abduction_finds_minimal_consistent_explanations() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule dead_battery: battery(flat) => no_start.
+             rule no_fuel: tank(empty) => no_start.
+             rule starter: starter(broken), battery(ok) => no_start.
+             rule lights: battery(flat) => lights(off).
+             rule fuel_gauge: tank(empty) => gauge(zero).",
+        )
+        .unwrap();
+    engine.add_fact("lights(on)");
+
+    let abduction = Abduction::new()
+        .with_abducible("battery(_)", 1.0)
+        .with_abducible("tank(empty)", 2.0)
+        .with_abducible("starter(broken)", 5.0)
+        .with_constraint(&["lights(on)", "lights(off)"])
+        .with_constraint(&["battery(flat)", "battery(ok)"]);
+
+    let explanations = abduction.explain(&engine, "no_start").unwrap();
+    let found: Vec<String> = explanations.iter().map(|e| e.to_string()).collect();
+    // A flat battery would turn the lights off, contradicting the observation
+    assert_eq!(
+        found,
+        vec![
+            "tank(empty) [cost 2]",
+            "battery(ok), starter(broken) [cost 6]"
+        ]
+    );
+    assert!(explanations[1].assumes("starter(broken)"));
+    assert_eq!(explanations[1].assumptions().len(), 2);
+
+    // Two observations need one explanation covering both
+    let both = abduction
+        .explain_all(&engine, &["no_start", "gauge(zero)"])
+        .unwrap();
+    assert_eq!(both.len(), 1);
+    assert!(both[0].assumes("tank(empty)"));
+
+    // Facts already explaining the observation need no assumptions
+    engine.add_fact("tank(empty)");
+    let known = abduction.explain(&engine, "no_start").unwrap();
+    assert_eq!(known.len(), 1);
+    assert!(known[0].assumptions().is_empty());
+
+    let mut starter = ReasoningEngine::new();
+    starter
+        .add_rules_from("rule starter: starter(broken), battery(ok) => no_start.")
+        .unwrap();
+    let limited = Abduction::new()
+        .with_abducible("starter(broken)", 1.0)
+        .with_abducible("battery(ok)", 1.0);
+    assert_eq!(limited.explain(&starter, "no_start").unwrap().len(), 1);
+    let limited = limited.with_max_assumptions(1);
+    assert!(limited.explain(&starter, "no_start").unwrap().is_empty());
+    assert!(abduction.explain(&engine, "bad(").is_err());
+}
+
+fn delivery(destination: &str, weight: &str) -> State {
+    State::new()
+        .set("destination", destination)
+        .set("weight", weight)
+}
+
+fn delivery_plan(destination: &str) -> Plan {
+    Plan::new("deliver")
+        .add_action(Action::new("load"))
+        .add_action(Action::new("drive").with_parameter(destination))
+        .add_action(Action::new("unload").with_parameter(destination))
+}
+
+#[test]
+fn case_based_retrieval_and_reuse() {
+    let similarity = Similarity::new()
+        .with_weight("destination", 1.0)
+        .with_weight("weight", 2.0)
+        .with_range("weight", 0.0, 100.0);
+    let mut cases = CaseBase::new(similarity).with_threshold(0.5);
+    assert!(cases.retain(Case::new(
+        delivery("depot", "10"),
+        delivery_plan("depot"),
+        Outcome::Success
+    )));
+    assert!(cases.retain(Case::new(
+        delivery("harbour", "80"),
+        delivery_plan("harbour"),
+        Outcome::Success
+    )));
+    // Failed cases are not kept by default
+    assert!(!cases.retain(Case::new(
+        delivery("airport", "20"),
+        delivery_plan("airport"),
+        Outcome::Failure
+    )));
+    assert_eq!(cases.len(), 2);
+
+    let problem = delivery("airport", "15");
+    let nearest = cases.retrieve(&problem, 1);
+    assert_eq!(nearest.len(), 1);
+    assert!(nearest[0].1.problem().matches("destination", "depot"));
+    assert!((nearest[0].0 - (2.0 * 0.95) / 3.0).abs() < 1e-9);
+
+    // The depot plan is adapted to the airport
+    let plan = cases.reuse(&problem).unwrap();
+    assert_eq!(plan.actions()[1].parameters(), ["airport"]);
+    assert_eq!(plan.actions()[2].parameters(), ["airport"]);
+    assert_eq!(cases.cases()[0].reuses(), 1);
+
+    // Nothing is similar enough to a very different problem
+    assert!(cases.reuse(&delivery("airport", "200")).is_none());
+}
+
+#[test]
+fn case_base_retention_policies_and_adaptation() {
+    let mut novel = CaseBase::new(Similarity::new()).with_retention(Retention::Novel(0.9));
+    assert!(novel.retain(Case::new(
+        delivery("depot", "10"),
+        delivery_plan("depot"),
+        Outcome::Success
+    )));
+    assert!(!novel.retain(Case::new(
+        delivery("depot", "10"),
+        delivery_plan("depot"),
+        Outcome::Success
+    )));
+    assert!(novel.retain(Case::new(
+        delivery("harbour", "10"),
+        delivery_plan("harbour"),
+        Outcome::Success
+    )));
+
+    // The least reused case is evicted when full
+    let mut bounded = CaseBase::default()
+        .with_retention(Retention::All)
+        .with_capacity(2);
+    bounded.retain(Case::new(
+        delivery("a", "1"),
+        delivery_plan("a"),
+        Outcome::Success,
+    ));
+    bounded.retain(Case::new(
+        delivery("b", "1"),
+        delivery_plan("b"),
+        Outcome::Failure,
+    ));
+    bounded.reuse(&delivery("a", "1")).unwrap();
+    bounded.retain(Case::new(
+        delivery("c", "1"),
+        delivery_plan("c"),
+        Outcome::Success,
+    ));
+    let kept: Vec<&str> = bounded
+        .cases()
+        .iter()
+        .map(|c| c.problem().get("destination").unwrap().as_str())
+        .collect();
+    assert_eq!(kept, vec!["a", "c"]);
+
+    let mut custom = CaseBase::default().with_adaptation(|case, problem| {
+        let mut plan = Plan::new(format!("{}_checked", case.solution().name()));
+        if problem.matches("fragile", "true") {
+            plan = plan.add_action(Action::new("pad"));
+        }
+        case.solution()
+            .actions()
+            .iter()
+            .fold(plan, |plan, action| plan.add_action(action.clone()))
+    });
+    custom.retain(Case::new(
+        delivery("a", "1"),
+        delivery_plan("a"),
+        Outcome::Success,
+    ));
+    let plan = custom
+        .reuse(&delivery("a", "1").set("fragile", "true"))
+        .unwrap();
+    assert_eq!(plan.name(), "deliver_checked");
+    assert_eq!(plan.actions()[0].name(), "pad");
+    assert_eq!(plan.len(), 4);
+}
+
+#[test]
+fn lint_reports_rule_base_problems() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule ancestor_base: parent(X, Y) => ancestor(X, Y).
+             rule ancestor_step: parent(X, Y), ancestor(Y, Z) => ancestor(X, Z).
+             rule elder: ancestor(X, Y), age(X, A), A > 60 => elder(X).
+             rule elder_copy: ancestor(X, Y), age(X, A), A > 60, knows(X, Y) => elder(X).
+             rule orphan: missing(X) => lonely(X).
+             rule broken: parent(X, Y) => related(X, Z).",
+        )
+        .unwrap();
+    engine.add_fact("parent(ann, bob)");
+    engine.add_fact("age(ann, 70)");
+    engine.add_fact("knows(ann, bob)");
+
+    let diagnostics = engine.lint();
+    let find = |kind: LintKind| -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter(|d| d.kind() == kind)
+            .map(|d| d.rules().join(" "))
+            .collect()
+    };
+    assert_eq!(find(LintKind::UnboundVariable), vec!["broken"]);
+    assert_eq!(find(LintKind::UnreachableRule), vec!["orphan"]);
+    assert_eq!(find(LintKind::SubsumedRule), vec!["elder_copy elder"]);
+    assert_eq!(find(LintKind::RecursiveCycle), vec!["ancestor_step"]);
+    assert_eq!(
+        find(LintKind::UnusedConclusion),
+        vec!["elder", "elder_copy", "orphan", "broken"]
+    );
+
+    let unbound = &diagnostics[0];
+    assert_eq!(unbound.severity(), Severity::Error);
+    assert_eq!(
+        unbound.to_string(),
+        "error[unbound-variable] broken: variable Z in conclusion `related(X,Z)` is never bound"
+    );
+    assert!(diagnostics
+        .iter()
+        .any(|d| d.message() == "no fact or rule provides `missing(X)`"));
+    assert!(diagnostics
+        .iter()
+        .any(|d| d.message() == "recursion through ancestor/2"));
+
+    // A clean rule base has nothing above info level
+    let mut clean = ReasoningEngine::new();
+    clean.add_rule(
+        Rule::new("wet")
+            .with_condition("rain")
+            .with_conclusion("wet"),
+    );
+    clean.add_fact("rain");
+    assert!(clean.lint().iter().all(|d| d.severity() == Severity::Info));
+}
+
+#[test]
+fn engine_explains_inferred_facts() {
+    let mut engine = ReasoningEngine::new();
+    engine
+        .add_rules_from(
+            "rule wet: rain => wet(grass).
+             rule slippery: wet(X) => slippery(X).",
+        )
+        .unwrap();
+    engine.add_fact("rain");
+    engine.infer();
+
+    let explanation = engine.explain("slippery(grass)").unwrap();
+    assert_eq!(explanation.reason(), &Reason::Rule("slippery".to_string()));
+    assert_eq!(
+        explanation.to_string(),
+        "slippery(grass) [rule slippery]\n  wet(grass) [rule wet]\n    rain [fact]\n"
+    );
+    assert_eq!(
+        explanation.to_json(),
+        concat!(
+            r#"{"subject":"slippery(grass)","reason":"rule","rule":"slippery","children":["#,
+            r#"{"subject":"wet(grass)","reason":"rule","rule":"wet","children":["#,
+            r#"{"subject":"rain","reason":"fact","children":[]}]}]}"#
+        )
+    );
+    assert_eq!(explanation.find("rain").unwrap().reason(), &Reason::Fact);
+    assert!(engine.explain("sunny").is_none());
+}
+
+#[test]
+fn rule_language_rejects_negation() {
+    let source = "rule alarm: hot,\n  not ventilated => alarm(high).";
+    let error = parse_rules(source).unwrap_err();
+    assert_eq!((error.line(), error.column()), (2, 3));
+    assert!(error.message().contains("DatalogProgram"));
+    let mut engine = ReasoningEngine::new();
+    assert!(engine.add_rules_from(source).is_err());
+    assert!(engine.rules().is_empty());
+    // Atoms that merely start with "not" are still conditions
+    assert_eq!(
+        parse_rules("rule r: nothing_wrong => ok.").unwrap()[0].conditions(),
+        ["nothing_wrong"]
+    );
+
+    // Negation as failure is evaluated by Datalog programs
+    let alarm = Rule::new("alarm")
+        .with_condition("hot")
+        .with_condition("not ventilated")
+        .with_conclusion("alarm(high)");
+    let mut program = DatalogProgram::new();
+    program.add_rule(alarm.clone());
+    program.add_fact("hot").unwrap();
+    assert!(program.evaluate().unwrap().contains("alarm(high)"));
+
+    let mut program = DatalogProgram::new();
+    program.add_rule(alarm.clone());
+    program.add_fact("hot").unwrap();
+    program.add_fact("ventilated").unwrap();
+    assert!(!program.evaluate().unwrap().contains("alarm(high)"));
+
+    // The engine cannot evaluate such a rule, and its lint says so
+    let mut engine = ReasoningEngine::new();
+    engine.add_rule(alarm);
+    engine.add_fact("hot");
+    engine.infer();
+    assert!(!engine.has_fact("alarm(high)"));
+    let negated: Vec<_> = engine
+        .lint()
+        .into_iter()
+        .filter(|d| d.kind() == LintKind::NegatedCondition)
+        .collect();
+    assert_eq!(negated.len(), 1);
+    assert_eq!(negated[0].severity(), Severity::Error);
+    assert_eq!(negated[0].rules(), ["alarm"]);
+    assert!(negated[0].message().contains("not ventilated"));
+}
+
+#[test]
+fn certainty_of_shared_antecedents_is_computed_once() {
+    // Every layer depends on both facts of the layer below: 2^30 paths
+    let mut engine = ReasoningEngine::new();
+    for i in 1..=30 {
+        for side in ["a", "b"] {
+            engine.add_rule(
+                Rule::new(format!("{}{}", side, i))
+                    .with_condition(format!("a{}", i - 1))
+                    .with_condition(format!("b{}", i - 1))
+                    .with_conclusion(format!("{}{}", side, i)),
+            );
+        }
+    }
+    engine.add_fact_with_certainty("a0", 0.9);
+    engine.add_fact_with_certainty("b0", 0.8);
+    assert_eq!(engine.infer().len(), 60);
+    assert!((engine.certainty("b30") - 0.8).abs() < 1e-9);
+}